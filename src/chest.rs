@@ -0,0 +1,226 @@
+use std::any::Any;
+
+use crate::container::*;
+use crate::inventory::Inventory;
+use crate::nbt::{Compound, Tag};
+use crate::world::*;
+
+pub const CHEST: &str = "chest";
+pub const CHEST_SIZE: usize = 27;
+const COLUMNS: usize = 9;
+const TITLE_HEIGHT: f32 = 18.0;
+
+// only these sides can join two chests together
+const PARTNER_SIDES: [Side; 4] = [Side::Left, Side::Right, Side::Front, Side::Back];
+
+// a chest placed next to a single chest joins it into a double chest, both halves keep
+// their own items and the menu shows them together
+pub struct Chest {
+    pub slots: Inventory,
+    pub partner: Option<Side>, // where the other half is
+    pub loot_table: Option<String>, // fills it the first time it is opened, for chests made with the world
+    pub loot_table_seed: u64, // 0 for a random one
+}
+
+impl Chest {
+    pub fn new() -> Self {
+        return Self {
+            slots: Inventory::new(CHEST_SIZE),
+            partner: None,
+            loot_table: None,
+            loot_table_seed: 0,
+        }
+    }
+    pub fn create() -> Box<dyn BlockEntity> {
+        return Box::new(Self::new());
+    }
+}
+
+fn chest_at(world: &World, pos: BlockPos) -> Option<&Chest> {
+    return world.block_entity(pos)?.as_any().downcast_ref();
+}
+
+// the other half of the chest at pos, only while both still point at each other so
+// breaking one half leaves a single chest
+pub fn partner(world: &World, pos: BlockPos) -> Option<BlockPos> {
+    let side = chest_at(world, pos)?.partner?;
+    let other = pos.offset(side);
+    if chest_at(world, other)?.partner != Some(side.opposite()) {
+        return None;
+    }
+    return Some(other);
+}
+
+impl BlockEntity for Chest {
+    fn placed(&mut self, pos: BlockPos, world: &mut World) {
+        // it came with a partner in its nbt
+        if self.partner.is_some() {
+            return;
+        }
+        for side in PARTNER_SIDES {
+            let other = pos.offset(side);
+            if chest_at(world, other).is_none() || partner(world, other).is_some() {
+                continue;
+            }
+            if let Some(chest) = world.block_entity_as::<Chest>(other) {
+                chest.partner = Some(side.opposite());
+                self.partner = Some(side);
+                return;
+            }
+        }
+    }
+    fn inventory(&self) -> Option<&Inventory> {
+        return Some(&self.slots);
+    }
+    fn take_loot_table(&mut self) -> Option<(String, u64)> {
+        return Some((self.loot_table.take()?, self.loot_table_seed));
+    }
+    fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        return Some(&mut self.slots);
+    }
+    fn menu(&self, pos: BlockPos, world: &World) -> Option<Menu> {
+        let halves = match partner(world, pos) {
+            // the half closer to the corner of the world goes on top
+            Some(other) if (other.x, other.z) < (pos.x, pos.z) => vec![other, pos],
+            Some(other) => vec![pos, other],
+            None => vec![pos],
+        };
+        let title = if halves.len() > 1 { "Large Chest" } else { "Chest" };
+        let mut containers = vec![ContainerRef::Player];
+        containers.extend(halves.iter().map(|x| ContainerRef::Block(*x)));
+        let mut menu = Menu::new(title, containers);
+        let rows = CHEST_SIZE / COLUMNS;
+        let mut ranges = Vec::new();
+        for half in 0..halves.len() {
+            let y = TITLE_HEIGHT + (half * rows) as f32 * SLOT_SPACING;
+            ranges.push(menu.add_grid(half + 1, 0, COLUMNS, rows, (8.0, y), SlotKind::Any));
+        }
+        menu.add_player_inventory(ranges);
+        return Some(menu);
+    }
+    fn save(&self, nbt: &mut Compound, world: &World) {
+        nbt.set("items", self.slots.save(world));
+        match self.partner.and_then(|x| Side::ALL.iter().position(|side| *side == x)) {
+            Some(side) => nbt.set("partner", Tag::Int(side as i32)),
+            None => {
+                nbt.remove("partner");
+            }
+        }
+        match &self.loot_table {
+            Some(table) => {
+                nbt.set("loot_table", Tag::String(table.clone()));
+                nbt.set("loot_table_seed", Tag::Long(self.loot_table_seed as i64));
+            }
+            None => {
+                nbt.remove("loot_table");
+                nbt.remove("loot_table_seed");
+            }
+        }
+    }
+    fn load(&mut self, nbt: &Compound, world: &World) {
+        if let Some(items) = nbt.get("items") {
+            self.slots.load(items, world);
+        }
+        self.partner = nbt.get_int("partner").and_then(|x| Side::ALL.get(x as usize)).copied();
+        self.loot_table = nbt.get_string("loot_table").map(|x| x.to_string());
+        self.loot_table_seed = match nbt.get("loot_table_seed") {
+            Some(Tag::Long(seed)) => *seed as u64,
+            _ => 0,
+        };
+    }
+    fn as_any(&self) -> &dyn Any {
+        return self;
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        return self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::ItemStack;
+    use crate::testing;
+
+    fn world() -> World {
+        let mut world = testing::world(&[CHEST], &[]);
+        world.blocks[1].block_entity = Some(Chest::create);
+        return world;
+    }
+
+    fn place(world: &mut World, pos: BlockPos, id: u64) {
+        world.place_block(pos.to_vec(), Block::new(id, NbtBlock::new(), world));
+    }
+
+    #[test]
+    fn adjacent_chests_pair_once() {
+        let mut world = world();
+        let (a, b, c) = (BlockPos::new(5, 1, 5), BlockPos::new(6, 1, 5), BlockPos::new(7, 1, 5));
+        place(&mut world, a, 1);
+        place(&mut world, b, 1);
+        place(&mut world, c, 1);
+        assert_eq!(partner(&world, a), Some(b));
+        assert_eq!(partner(&world, b), Some(a));
+        assert_eq!(partner(&world, c), None);
+        let menu = world.block_entity(b).unwrap().menu(b, &world).unwrap();
+        assert_eq!(menu.containers, vec![ContainerRef::Player, ContainerRef::Block(a), ContainerRef::Block(b)]);
+        assert_eq!(menu.slots.len(), CHEST_SIZE * 2 + 36);
+
+        // breaking a half leaves a single chest that can pair again
+        place(&mut world, a, 0);
+        assert_eq!(partner(&world, b), None);
+        place(&mut world, BlockPos::new(6, 1, 6), 1);
+        assert_eq!(partner(&world, BlockPos::new(6, 1, 6)), Some(b));
+    }
+
+    #[test]
+    fn contents_and_pairing_survive_saving() {
+        let mut world = world();
+        let (a, b) = (BlockPos::new(1, 1, 1), BlockPos::new(1, 1, 2));
+        place(&mut world, a, 1);
+        place(&mut world, b, 1);
+        world.block_entity_as::<Chest>(b).unwrap().slots.slots[4] = Some(ItemStack::new(1, 5, 64));
+        let path = testing::temp_path("chest_save_test");
+        world.save(&path).unwrap();
+        let mut loaded = self::world();
+        let result = loaded.load(&path);
+        std::fs::remove_file(&path).ok();
+        result.unwrap();
+        assert_eq!(partner(&loaded, a), Some(b));
+        assert_eq!(loaded.block_entity_as::<Chest>(b).unwrap().slots.slots[4].as_ref().unwrap().count, 5);
+    }
+
+    #[test]
+    fn loot_fills_once_and_the_same_for_a_seed() {
+        let mut world = world();
+        let table = r#"{ "pools": [{ "rolls": 3, "entries": [{ "type": "minecraft:item", "name": "minecraft:chest", "functions": [{ "function": "minecraft:set_count", "count": { "min": 1, "max": 64 } }] }] }] }"#;
+        world.loot_tables.insert("minecraft:chests/test".to_string(), crate::loot::parse_loot_table(&world, &crate::json::parse(table).unwrap()).unwrap());
+        let mut fill = |pos: BlockPos| {
+            let mut nbt = NbtBlock::new();
+            nbt.data.set("loot_table", Tag::String("minecraft:chests/test".to_string()));
+            nbt.data.set("loot_table_seed", Tag::Long(42));
+            world.place_block(pos.to_vec(), Block::new(1, nbt, &world));
+            world.unpack_loot(pos);
+            let slots = world.block_entity_as::<Chest>(pos).unwrap().slots.slots.clone();
+            // a second time does nothing
+            world.unpack_loot(pos);
+            assert_eq!(world.block_entity_as::<Chest>(pos).unwrap().slots.slots, slots);
+            return slots;
+        };
+        let slots = fill(BlockPos::new(1, 1, 1));
+        assert!(slots.iter().flatten().count() >= 3);
+        assert_eq!(fill(BlockPos::new(5, 1, 5)), slots);
+    }
+
+    #[test]
+    fn generated_chests_pair_when_placed() {
+        let mut world = world();
+        let (a, b) = (BlockPos::new(2, 1, 2), BlockPos::new(3, 1, 2));
+        place(&mut world, a, 1);
+        let mut nbt = NbtBlock::new();
+        nbt.data.set("loot_table", Tag::String("minecraft:chests/test".to_string()));
+        world.place_block(b.to_vec(), Block::new(1, nbt, &world));
+        assert_eq!(partner(&world, a), Some(b));
+        assert_eq!(partner(&world, b), Some(a));
+    }
+}
@@ -0,0 +1,457 @@
+use std::ops::Range;
+
+use crate::crafting::CraftingGrid;
+use crate::graphics::Vec3;
+use crate::inventory::{Inventory, HOTBAR_SIZE, PLAYER_INVENTORY_SIZE};
+use crate::items::ItemStack;
+use crate::player::Player;
+use crate::world::*;
+
+// menu sizes and positions are in hud pixels from the menu's top left
+pub const SLOT_SPACING: f32 = 18.0;
+pub const MENU_WIDTH: f32 = 176.0;
+const MENU_EDGE: f32 = 8.0;
+const HOTBAR_GAP: f32 = 4.0; // between the rest of the player's inventory and the hotbar
+const PLAYER_INVENTORY_HEIGHT: f32 = SLOT_SPACING * 4.0 + HOTBAR_GAP + MENU_EDGE;
+const MAX_DISTANCE: f32 = 8.0; // menus close when the player gets further than this from the block
+
+// what a menu's slots show the items of
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ContainerRef {
+    Player,
+    Block(BlockPos),
+    Crafting, // the menu's own crafting grid
+    CraftingResult, // a single slot with what the grid makes
+}
+
+// what a slot lets the player put in it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SlotKind {
+    Any,
+    TakeOnly, // results, like the output of a furnace
+    Fuel,
+    Smeltable,
+}
+
+impl SlotKind {
+    pub fn accepts(&self, stack: &ItemStack, world: &World) -> bool {
+        match self {
+            Self::Any => true,
+            Self::TakeOnly => false,
+            Self::Fuel => world.items[stack.item as usize].burn_time > 0,
+            Self::Smeltable => world.recipes.find_smelting(stack).is_some(),
+        }
+    }
+}
+
+pub struct Slot {
+    pub container: usize, // in the menu's containers
+    pub index: usize, // in the container's inventory
+    pub x: f32,
+    pub y: f32,
+    pub kind: SlotKind,
+}
+
+// shift clicking a slot in from moves its items into the first of the to ranges that
+// has room, all ranges are of the menu's slots
+pub struct QuickMove {
+    pub from: Range<usize>,
+    pub to: Vec<Range<usize>>,
+}
+
+// a bar filled from 0 to 1 by the block entity's progress, like a furnace's flame
+pub struct ProgressBar {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+// spreading the carried stack over the slots the mouse moved over while held
+struct Drag {
+    single: bool, // one item to every slot instead of an even split
+    slots: Vec<usize>,
+}
+
+pub struct Menu {
+    pub title: String,
+    pub containers: Vec<ContainerRef>,
+    pub slots: Vec<Slot>,
+    pub quick_moves: Vec<QuickMove>,
+    pub bars: Vec<ProgressBar>,
+    pub height: f32,
+    pub carried: Option<ItemStack>, // on the cursor
+    pub crafting: Option<CraftingGrid>, // its items go back to the player when the menu closes
+    pub opened_at: Option<(BlockPos, u64)>, // a block without a block entity it was opened from, and its id
+    drag: Option<Drag>,
+}
+
+impl Menu {
+    pub fn new(title: &str, containers: Vec<ContainerRef>) -> Self {
+        return Self {
+            title: title.to_string(),
+            containers,
+            slots: Vec::new(),
+            quick_moves: Vec::new(),
+            bars: Vec::new(),
+            height: MENU_EDGE,
+            carried: None,
+            crafting: None,
+            opened_at: None,
+            drag: None,
+        }
+    }
+    // a grid of slots for consecutive items of a container with its top left at pos,
+    // returns the menu slots it added
+    pub fn add_grid(&mut self, container: usize, first: usize, columns: usize, rows: usize, pos: (f32, f32), kind: SlotKind) -> Range<usize> {
+        let (x, y) = pos;
+        let start = self.slots.len();
+        for row in 0..rows {
+            for column in 0..columns {
+                self.slots.push(Slot {
+                    container,
+                    index: first + row * columns + column,
+                    x: x + column as f32 * SLOT_SPACING,
+                    y: y + row as f32 * SLOT_SPACING,
+                    kind,
+                });
+            }
+        }
+        self.height = self.height.max(y + rows as f32 * SLOT_SPACING);
+        return start..self.slots.len();
+    }
+    // the player's inventory under everything else, shift clicking moves between it and
+    // the block's slots, or between the hotbar and the rest when the block takes nothing
+    pub fn add_player_inventory(&mut self, block_slots: Vec<Range<usize>>) {
+        let player = self.containers.iter().position(|x| *x == ContainerRef::Player).unwrap_or(0);
+        let top = self.height + MENU_EDGE;
+        let rows = (PLAYER_INVENTORY_SIZE - HOTBAR_SIZE) / HOTBAR_SIZE;
+        let main = self.add_grid(player, HOTBAR_SIZE, HOTBAR_SIZE, rows, (MENU_EDGE, top), SlotKind::Any);
+        let hotbar = self.add_grid(player, 0, HOTBAR_SIZE, 1, (MENU_EDGE, top + rows as f32 * SLOT_SPACING + HOTBAR_GAP), SlotKind::Any);
+        self.height = top + PLAYER_INVENTORY_HEIGHT;
+        for range in block_slots.iter() {
+            self.quick_moves.push(QuickMove {
+                from: range.clone(),
+                to: vec![main.clone(), hotbar.clone()],
+            });
+        }
+        let mut to_main = block_slots.clone();
+        to_main.push(main.clone());
+        let mut to_hotbar = block_slots;
+        to_hotbar.push(hotbar.clone());
+        self.quick_moves.push(QuickMove { from: main, to: to_hotbar });
+        self.quick_moves.push(QuickMove { from: hotbar, to: to_main });
+    }
+    pub fn is_dragging(&self) -> bool {
+        return self.drag.is_some();
+    }
+    fn stack<'a>(&self, slot: usize, inventories: &'a mut [Inventory]) -> &'a mut Option<ItemStack> {
+        let slot = &self.slots[slot];
+        return &mut inventories[slot.container].slots[slot.index];
+    }
+    // pressing a mouse button on a slot, left takes or starts a drag and right takes half
+    // or starts dropping one at a time, returns whether it took out of a result slot
+    pub fn press(&mut self, slot: Option<usize>, right: bool, shift: bool, inventories: &mut [Inventory], world: &World) -> bool {
+        let Some(slot) = slot else {
+            return false;
+        };
+        if shift {
+            if self.carried.is_none() {
+                return self.quick_move(slot, inventories, world);
+            }
+            return false;
+        }
+        if self.carried.is_some() {
+            self.drag = Some(Drag { single: right, slots: Vec::new() });
+            self.drag_over(slot, inventories, world);
+            return false;
+        }
+        let Slot { container, index, kind, .. } = self.slots[slot];
+        let inventory = &mut inventories[container];
+        // a crafting result is only ever taken whole
+        let half = right && self.containers[container] != ContainerRef::CraftingResult;
+        self.carried = if half { inventory.split(index) } else { inventory.slots[index].take() };
+        return self.carried.is_some() && kind == SlotKind::TakeOnly;
+    }
+    // the mouse moved over a slot while a button is held
+    pub fn drag_over(&mut self, slot: usize, inventories: &mut [Inventory], world: &World) {
+        let Some(carried) = &self.carried else {
+            return;
+        };
+        let fits = match self.stack(slot, inventories) {
+            None => self.slots[slot].kind.accepts(carried, world),
+            Some(stack) => stack.can_merge(carried) && !stack.is_full() && self.slots[slot].kind.accepts(carried, world),
+        };
+        let Some(drag) = &mut self.drag else {
+            return;
+        };
+        // every slot gets at least one item
+        if fits && !drag.slots.contains(&slot) && (drag.slots.len() as u32) < carried.count {
+            drag.slots.push(slot);
+        }
+    }
+    // letting go of the mouse button, a drag over one slot is just a click on it
+    pub fn release(&mut self, slot: Option<usize>, inventories: &mut [Inventory], world: &World) -> bool {
+        let Some(drag) = self.drag.take() else {
+            return false;
+        };
+        if drag.slots.len() <= 1 {
+            let Some(slot) = drag.slots.first().copied().or(slot) else {
+                return false;
+            };
+            return self.click(slot, drag.single, inventories, world);
+        }
+        let Some(carried) = &mut self.carried else {
+            return false;
+        };
+        let each = if drag.single { 1 } else { carried.count / drag.slots.len() as u32 };
+        for &slot in drag.slots.iter() {
+            let slot = &self.slots[slot];
+            // only slots it merges into were dragged over, so nothing gets swapped out
+            if let Some(rest) = inventories[slot.container].place(slot.index, carried.split(each)) {
+                carried.count += rest.count;
+            }
+        }
+        if carried.is_empty() {
+            self.carried = None;
+        }
+        return false;
+    }
+    // a carried stack goes into the slot, or one of it with single, swapping with what is
+    // there when it's something else, and result slots add themselves to it instead
+    fn click(&mut self, slot: usize, single: bool, inventories: &mut [Inventory], world: &World) -> bool {
+        let kind = self.slots[slot].kind;
+        let Some(mut carried) = self.carried.take() else {
+            return false;
+        };
+        let stack = self.stack(slot, inventories);
+        if kind == SlotKind::TakeOnly {
+            let took = match stack {
+                Some(existing) if existing.can_merge(&carried) && carried.count + existing.count <= carried.max_stack => {
+                    carried.merge(existing);
+                    *stack = None;
+                    true
+                }
+                _ => false,
+            };
+            self.carried = Some(carried);
+            return took;
+        }
+        // one item can't be swapped with something else
+        if !kind.accepts(&carried, world) || (single && stack.as_ref().is_some_and(|x| !x.can_merge(&carried))) {
+            self.carried = Some(carried);
+            return false;
+        }
+        let count = if single { 1 } else { carried.count };
+        let slot = &self.slots[slot];
+        // what comes back is either the part that didn't fit or whatever was swapped out
+        if let Some(back) = inventories[slot.container].place(slot.index, carried.split(count)) {
+            if carried.is_empty() {
+                carried = back;
+            } else {
+                carried.count += back.count;
+            }
+        }
+        if !carried.is_empty() {
+            self.carried = Some(carried);
+        }
+        return false;
+    }
+    // shift clicking, fills stacks of the same item in each target range before its empty slots.
+    // the slots of a range that accept the item are gathered into an inventory of their own
+    // to transfer into, then put back
+    fn quick_move(&mut self, slot: usize, inventories: &mut [Inventory], world: &World) -> bool {
+        let Some(rule) = self.quick_moves.iter().find(|x| x.from.contains(&slot)) else {
+            return false;
+        };
+        let Some(count) = self.stack(slot, inventories).as_ref().map(|x| x.count) else {
+            return false;
+        };
+        if self.containers[self.slots[slot].container] == ContainerRef::CraftingResult && self.room(slot, &rule.to, inventories, world) < count {
+            return false;
+        }
+        let mut moving = Inventory::new(1);
+        moving.slots[0] = self.stack(slot, inventories).take();
+        for range in rule.to.iter() {
+            let Some(stack) = &moving.slots[0] else {
+                break;
+            };
+            let targets: Vec<usize> = range.clone().filter(|x| self.slots[*x].kind.accepts(stack, world)).collect();
+            let mut gathered = Inventory::new(0);
+            gathered.slots = targets.iter().map(|x| self.stack(*x, inventories).take()).collect();
+            moving.transfer(0, &mut gathered);
+            for (target, stack) in targets.iter().zip(gathered.slots) {
+                *self.stack(*target, inventories) = stack;
+            }
+        }
+        let left = moving.slots[0].take();
+        let moved = left.as_ref().map_or(0, |x| x.count) < count;
+        *self.stack(slot, inventories) = left;
+        return moved && self.slots[slot].kind == SlotKind::TakeOnly;
+    }
+    // how many more of a slot's items the slots in the ranges can take
+    fn room(&self, slot: usize, ranges: &[Range<usize>], inventories: &mut [Inventory], world: &World) -> u32 {
+        let Some(stack) = self.stack(slot, inventories).clone() else {
+            return 0;
+        };
+        let mut room = 0;
+        for target in ranges.iter().flat_map(|x| x.clone()) {
+            if !self.slots[target].kind.accepts(&stack, world) {
+                continue;
+            }
+            room += match self.stack(target, inventories) {
+                Some(other) if other.can_merge(&stack) => other.max_stack.saturating_sub(other.count),
+                Some(_) => 0,
+                None => stack.max_stack,
+            };
+        }
+        return room;
+    }
+    // the crafting result was taken out, so the grid's ingredients get used up
+    pub fn crafted(&mut self, world: &World) {
+        if let Some(grid) = &mut self.crafting {
+            grid.craft(&world.recipes, world);
+        }
+    }
+    // what the cursor was holding and whatever is left in the crafting grid, to go back to the player
+    pub fn close(&mut self) -> Vec<ItemStack> {
+        self.drag = None;
+        let mut stacks: Vec<ItemStack> = self.carried.take().into_iter().collect();
+        if let Some(grid) = &mut self.crafting {
+            stacks.extend(grid.inventory.slots.iter_mut().filter_map(|x| x.take()));
+        }
+        return stacks;
+    }
+    // the stack a slot shows, for drawing it
+    pub fn shown(&self, slot: usize, player: &Player, world: &World) -> Option<ItemStack> {
+        let slot = &self.slots[slot];
+        let inventory = match self.containers[slot.container] {
+            ContainerRef::Player => Some(&player.inventory),
+            ContainerRef::Block(pos) => world.block_entity(pos).and_then(|x| x.inventory()),
+            ContainerRef::Crafting => self.crafting.as_ref().map(|x| &x.inventory),
+            ContainerRef::CraftingResult => return self.crafting.as_ref()?.result(&world.recipes, world),
+        };
+        return inventory?.slots.get(slot.index)?.clone();
+    }
+    // the menu closes when a block it shows is gone or the player walked away
+    pub fn still_valid(&self, world: &World, player: &Player) -> bool {
+        let near = |pos: BlockPos| (pos.to_vec() - player.eye_pos()).magnitude() <= MAX_DISTANCE;
+        if let Some((pos, id)) = self.opened_at {
+            if world.block_at(pos.to_vec()).is_none_or(|x| x.get_id() != id) || !near(pos) {
+                return false;
+            }
+        }
+        return self.containers.iter().all(|container| match container {
+            ContainerRef::Block(pos) => world.block_entity(*pos).is_some_and(|x| x.inventory().is_some()) && near(*pos),
+            _ => true,
+        });
+    }
+    // moves every inventory the menu shows out of the player, the block entities and the
+    // crafting grid so they can all be changed together, then puts them back. the crafting
+    // result is made up from the grid each time and thrown away after
+    pub fn with_inventories<R>(&mut self, player: &mut Inventory, world: &mut World, f: impl FnOnce(&mut Menu, &mut [Inventory], &World) -> R) -> R {
+        // worked out before the grid is moved out
+        let crafted = self.crafting.as_ref().and_then(|x| x.result(&world.recipes, world));
+        let mut inventories = Vec::new();
+        for container in self.containers.iter() {
+            let inventory = match container {
+                ContainerRef::Player => Some(&mut *player),
+                ContainerRef::Block(pos) => world.block_entity_mut(*pos).and_then(|x| x.inventory_mut()),
+                ContainerRef::Crafting => self.crafting.as_mut().map(|x| &mut x.inventory),
+                ContainerRef::CraftingResult => {
+                    let mut result = Inventory::new(1);
+                    result.slots[0] = crafted.clone();
+                    inventories.push(result);
+                    continue;
+                }
+            };
+            inventories.push(inventory.map_or(Inventory::new(0), |x| std::mem::replace(x, Inventory::new(0))));
+        }
+        let result = f(self, &mut inventories, world);
+        for (container, inventory) in self.containers.iter().zip(inventories) {
+            let target = match container {
+                ContainerRef::Player => Some(&mut *player),
+                ContainerRef::Block(pos) => world.block_entity_mut(*pos).and_then(|x| x.inventory_mut()),
+                ContainerRef::Crafting => self.crafting.as_mut().map(|x| &mut x.inventory),
+                ContainerRef::CraftingResult => None,
+            };
+            if let Some(target) = target {
+                *target = inventory;
+            }
+        }
+        return result;
+    }
+}
+
+// on_use for blocks whose block entity has a menu, the player opens it
+pub fn open_menu(pos: Vec3, _block: &mut Block, world: &mut World, block_use: &mut BlockUse) -> bool {
+    let pos = BlockPos::from_vec(pos);
+    let Some(menu) = world.block_entity(pos).and_then(|x| x.menu(pos, world)) else {
+        return false;
+    };
+    // every part of a double chest gets its loot
+    for container in menu.containers.iter() {
+        if let ContainerRef::Block(pos) = container {
+            world.unpack_loot(*pos);
+        }
+    }
+    block_use.player.menu = Some(menu);
+    return true;
+}
+
+// the menu slot of a point in the menu, for finding what the mouse is over
+pub fn slot_at(menu: &Menu, x: f32, y: f32) -> Option<usize> {
+    return menu.slots.iter().position(|slot| x >= slot.x && x < slot.x + SLOT_SPACING && y >= slot.y && y < slot.y + SLOT_SPACING);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stone(count: u32) -> Option<ItemStack> {
+        return Some(ItemStack::new(1, count, 64));
+    }
+
+    // a row of three slots for a block over the player's inventory
+    fn menu() -> (Menu, Vec<Inventory>) {
+        let mut menu = Menu::new("Test", vec![ContainerRef::Player, ContainerRef::Block(BlockPos::new(0, 0, 0))]);
+        let block = menu.add_grid(1, 0, 3, 1, (MENU_EDGE, MENU_EDGE), SlotKind::Any);
+        menu.add_player_inventory(vec![block]);
+        return (menu, vec![Inventory::new(PLAYER_INVENTORY_SIZE), Inventory::new(3)]);
+    }
+
+    #[test]
+    fn split_drag_and_swap() {
+        let world = World::new();
+        let (mut menu, mut inventories) = menu();
+        inventories[1].slots[0] = stone(9);
+        menu.press(Some(0), true, false, &mut inventories, &world);
+        assert_eq!((menu.carried.clone(), inventories[1].slots[0].clone()), (stone(5), stone(4)));
+
+        // dragging over two slots spreads the stack evenly and keeps the rest
+        menu.press(Some(1), false, false, &mut inventories, &world);
+        menu.drag_over(2, &mut inventories, &world);
+        menu.release(Some(2), &mut inventories, &world);
+        assert_eq!((inventories[1].slots[1].clone(), inventories[1].slots[2].clone()), (stone(2), stone(2)));
+        assert_eq!(menu.carried, stone(1));
+
+        // a different item is swapped with what is carried
+        inventories[1].slots[0] = Some(ItemStack::new(2, 3, 16));
+        menu.press(Some(0), false, false, &mut inventories, &world);
+        menu.release(Some(0), &mut inventories, &world);
+        assert_eq!(inventories[1].slots[0], stone(1));
+        assert_eq!(menu.carried, Some(ItemStack::new(2, 3, 16)));
+    }
+
+    #[test]
+    fn shift_click_fills_stacks_before_empty_slots() {
+        let world = World::new();
+        let (mut menu, mut inventories) = menu();
+        inventories[0].slots[HOTBAR_SIZE + 5] = stone(60);
+        inventories[1].slots[0] = stone(10);
+        menu.press(Some(0), false, true, &mut inventories, &world);
+        assert_eq!(inventories[0].slots[HOTBAR_SIZE + 5], stone(64));
+        assert_eq!(inventories[0].slots[HOTBAR_SIZE], stone(6));
+        assert!(inventories[1].slots[0].is_none());
+    }
+}
@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+
+use crate::container::*;
+use crate::graphics::{files, Vec3};
+use crate::inventory::Inventory;
+use crate::items::ItemStack;
+use crate::json::{self, Json};
+use crate::world::{Block, BlockPos, BlockUse, World};
+
+pub const CRAFTING_TABLE: &str = "crafting table";
+pub const RECIPE_DIR: &str = "./data/recipes";
+pub const ITEM_TAG_DIR: &str = "./data/tags/items";
+const MAX_TAG_DEPTH: u32 = 16; // tags including tags including ... gives up after this
+const DEFAULT_COOKING_TIME: u32 = 200; // ticks
+
+// the items that can go in one spot of a recipe
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ingredient {
+    pub items: Vec<u64>,
+}
+
+impl Ingredient {
+    pub fn matches(&self, stack: &ItemStack) -> bool {
+        return self.items.contains(&stack.item);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum RecipeKind {
+    // a pattern row by row with empty spots as None, can also be crafted mirrored
+    Shaped {
+        width: usize,
+        height: usize,
+        pattern: Vec<Option<Ingredient>>,
+    },
+    // the ingredients can be anywhere in the grid
+    Shapeless(Vec<Ingredient>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Recipe {
+    pub name: String,
+    pub kind: RecipeKind,
+    pub result: u64,
+    pub count: u32,
+}
+
+impl Recipe {
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match &self.kind {
+            RecipeKind::Shaped { width, height, pattern } => {
+                let Some((x, y, w, h)) = grid.bounds() else {
+                    return false;
+                };
+                if (w, h) != (*width, *height) {
+                    return false;
+                }
+                let fits = |mirrored: bool| (0..h).all(|j| (0..w).all(|i| {
+                    let spot = &pattern[j * w + if mirrored { w - 1 - i } else { i }];
+                    match (spot, grid.get(x + i, y + j)) {
+                        (None, None) => true,
+                        (Some(ingredient), Some(stack)) => ingredient.matches(stack),
+                        _ => false,
+                    }
+                }));
+                return fits(false) || fits(true);
+            }
+            RecipeKind::Shapeless(ingredients) => {
+                let stacks: Vec<&ItemStack> = grid.inventory.slots.iter().flatten().collect();
+                if stacks.len() != ingredients.len() {
+                    return false;
+                }
+                return assign(&stacks, ingredients, &mut vec![false; ingredients.len()]);
+            }
+        }
+    }
+}
+
+// whether every stack can take a different ingredient, backtracking when a stack
+// took an ingredient that a later stack needed
+fn assign(stacks: &[&ItemStack], ingredients: &[Ingredient], used: &mut Vec<bool>) -> bool {
+    let Some((stack, rest)) = stacks.split_first() else {
+        return true;
+    };
+    for (i, ingredient) in ingredients.iter().enumerate() {
+        if !used[i] && ingredient.matches(stack) {
+            used[i] = true;
+            if assign(rest, ingredients, used) {
+                return true;
+            }
+            used[i] = false;
+        }
+    }
+    return false;
+}
+
+// cooking one item into another in a furnace
+#[derive(Clone, PartialEq, Debug)]
+pub struct SmeltingRecipe {
+    pub name: String,
+    pub ingredient: Ingredient,
+    pub result: u64,
+    pub count: u32,
+    pub experience: f32, // given to whoever takes the result out
+    pub cooking_time: u32, // ticks
+}
+
+// every recipe, also indexed by what they make for the recipe book
+#[derive(Default)]
+pub struct RecipeBook {
+    pub recipes: Vec<Recipe>,
+    pub smelting: Vec<SmeltingRecipe>,
+    by_result: HashMap<u64, Vec<usize>>,
+}
+
+impl RecipeBook {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+    pub fn add(&mut self, recipe: Recipe) {
+        self.by_result.entry(recipe.result).or_default().push(self.recipes.len());
+        self.recipes.push(recipe);
+    }
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        return self.recipes.iter().find(|x| x.matches(grid));
+    }
+    pub fn for_result(&self, item: u64) -> Vec<&Recipe> {
+        return self.by_result.get(&item).map_or(Vec::new(), |x| x.iter().map(|&i| &self.recipes[i]).collect());
+    }
+    pub fn add_smelting(&mut self, recipe: SmeltingRecipe) {
+        self.smelting.push(recipe);
+    }
+    pub fn find_smelting(&self, stack: &ItemStack) -> Option<&SmeltingRecipe> {
+        return self.smelting.iter().find(|x| x.ingredient.matches(stack));
+    }
+}
+
+// the 2x2 grid in the inventory or the 3x3 one of a crafting table
+pub struct CraftingGrid {
+    pub width: usize,
+    pub height: usize,
+    pub inventory: Inventory, // row by row
+}
+
+impl CraftingGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        return Self {
+            width,
+            height,
+            inventory: Inventory::new(width * height),
+        }
+    }
+    pub fn get(&self, x: usize, y: usize) -> Option<&ItemStack> {
+        return self.inventory.slots[y * self.width + x].as_ref();
+    }
+    // the smallest rectangle around the filled slots as x, y, width and height
+    fn bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let filled = |x: usize, y: usize| self.get(x, y).is_some();
+        let columns: Vec<usize> = (0..self.width).filter(|&x| (0..self.height).any(|y| filled(x, y))).collect();
+        let rows: Vec<usize> = (0..self.height).filter(|&y| (0..self.width).any(|x| filled(x, y))).collect();
+        let (x, y) = (*columns.first()?, *rows.first()?);
+        return Some((x, y, columns.last()? - x + 1, rows.last()? - y + 1));
+    }
+    // what would be crafted, shown in the result slot
+    pub fn result(&self, book: &RecipeBook, world: &World) -> Option<ItemStack> {
+        let recipe = book.find(self)?;
+        return Some(world.item_stack(recipe.result, recipe.count));
+    }
+    // takes one item out of every slot and gives back the result
+    pub fn craft(&mut self, book: &RecipeBook, world: &World) -> Option<ItemStack> {
+        let result = self.result(book, world)?;
+        for slot in 0..self.inventory.slots.len() {
+            self.inventory.remove(slot, 1);
+        }
+        return Some(result);
+    }
+}
+
+// a crafting grid with its result to the right, over the player's inventory. shift clicking
+// moves out of the grid but never into it
+fn crafting_menu(title: &str, size: usize, grid_pos: (f32, f32), result_pos: (f32, f32)) -> Menu {
+    let mut menu = Menu::new(title, vec![ContainerRef::Player, ContainerRef::Crafting, ContainerRef::CraftingResult]);
+    menu.crafting = Some(CraftingGrid::new(size, size));
+    let grid = menu.add_grid(1, 0, size, size, grid_pos, SlotKind::Any);
+    let result = menu.add_grid(2, 0, 1, 1, result_pos, SlotKind::TakeOnly);
+    let start = menu.slots.len();
+    menu.add_player_inventory(Vec::new());
+    let player = start..menu.slots.len();
+    menu.quick_moves.push(QuickMove { from: grid, to: vec![player.clone()] });
+    menu.quick_moves.push(QuickMove { from: result, to: vec![player] });
+    return menu;
+}
+
+// the player's own inventory with the 2x2 grid
+pub fn inventory_menu() -> Menu {
+    return crafting_menu("Crafting", 2, (98.0, 18.0), (154.0, 28.0));
+}
+
+// on_use for the crafting table, the player opens its 3x3 grid
+pub fn open_crafting_table(pos: Vec3, block: &mut Block, _world: &mut World, block_use: &mut BlockUse) -> bool {
+    let mut menu = crafting_menu("Crafting", 3, (30.0, 17.0), (124.0, 35.0));
+    menu.opened_at = Some((BlockPos::from_vec(pos), block.get_id()));
+    block_use.player.menu = Some(menu);
+    return true;
+}
+
+// item tags are lists of items and other tags by id, saved as data/tags/items/<name>.json
+pub fn load_item_tags(world: &mut World, dev: &bool) {
+    let mut raw = HashMap::new();
+    for path in files::list_files(ITEM_TAG_DIR, dev).unwrap_or_default() {
+        let Some(name) = path.rsplit('/').next().and_then(|x| x.strip_suffix(".json")) else {
+            continue;
+        };
+        match files::load_file(&path, dev).map_err(|e| e.to_string()).and_then(|x| json::parse(&x)) {
+            Ok(tag) => {
+                let values = tag.get("values").and_then(|x| x.as_array()).cloned().unwrap_or_default();
+                raw.insert(format!("minecraft:{}", name), values);
+            }
+            Err(e) => eprintln!("failed to load {}: {}", path, e),
+        }
+    }
+    for name in raw.keys() {
+        let items = resolve_tag(world, &raw, name, 0);
+        world.item_tags.insert(name.clone(), items);
+    }
+}
+
+fn resolve_tag(world: &World, raw: &HashMap<String, Vec<Json>>, name: &str, depth: u32) -> Vec<u64> {
+    let mut items = Vec::new();
+    if depth > MAX_TAG_DEPTH {
+        eprintln!("item tag {} includes itself", name);
+        return items;
+    }
+    for value in raw.get(name).into_iter().flatten() {
+        // entries are either ids or objects with an id that may be missing
+        let (id, required) = match value {
+            Json::Object(_) => (value.get("id").and_then(|x| x.as_str()), value.get("required").and_then(|x| x.as_bool()).unwrap_or(true)),
+            _ => (value.as_str(), true),
+        };
+        let Some(id) = id else {
+            continue;
+        };
+        if let Some(tag) = id.strip_prefix('#') {
+            items.extend(resolve_tag(world, raw, &namespaced(tag), depth + 1));
+        } else if let Some(item) = world.find_item(id) {
+            items.push(item);
+        } else if required {
+            eprintln!("unknown item {} in item tag {}", id, name);
+        }
+    }
+    items.sort_unstable();
+    items.dedup();
+    return items;
+}
+
+// recipes use the same json as minecraft, the name is the file name
+pub fn load_recipes(world: &mut World, dev: &bool) {
+    for path in files::list_files(RECIPE_DIR, dev).unwrap_or_default() {
+        let Some(name) = path.rsplit('/').next().and_then(|x| x.strip_suffix(".json")) else {
+            continue;
+        };
+        let name = format!("minecraft:{}", name);
+        let json = match files::load_file(&path, dev).map_err(|e| e.to_string()).and_then(|x| json::parse(&x)) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("failed to load {}: {}", path, e);
+                continue;
+            }
+        };
+        match json.get("type").and_then(|x| x.as_str()).map(namespaced).as_deref() {
+            Some("minecraft:smelting") => match parse_smelting(world, &name, &json) {
+                Ok(recipe) => world.recipes.add_smelting(recipe),
+                Err(e) => eprintln!("failed to load {}: {}", path, e),
+            },
+            _ => match parse_recipe(world, &name, &json) {
+                Ok(Some(recipe)) => world.recipes.add(recipe),
+                Ok(None) => (),
+                Err(e) => eprintln!("failed to load {}: {}", path, e),
+            },
+        }
+    }
+}
+
+// None for recipe types that aren't crafting, like smelting
+pub fn parse_recipe(world: &World, name: &str, json: &Json) -> Result<Option<Recipe>, String> {
+    let kind = match json.get("type").and_then(|x| x.as_str()).map(namespaced).as_deref() {
+        Some("minecraft:crafting_shaped") => parse_shaped(world, json)?,
+        Some("minecraft:crafting_shapeless") => {
+            let ingredients = json.get("ingredients").and_then(|x| x.as_array()).ok_or("missing ingredients")?;
+            if ingredients.is_empty() || ingredients.len() > 9 {
+                return Err("a shapeless recipe needs 1 to 9 ingredients".to_string());
+            }
+            RecipeKind::Shapeless(ingredients.iter().map(|x| parse_ingredient(world, x)).collect::<Result<_, _>>()?)
+        }
+        Some(_) => return Ok(None),
+        None => return Err("missing type".to_string()),
+    };
+    let (result, count) = parse_result(world, json)?;
+    return Ok(Some(Recipe {
+        name: name.to_string(),
+        kind,
+        result,
+        count,
+    }));
+}
+
+pub fn parse_smelting(world: &World, name: &str, json: &Json) -> Result<SmeltingRecipe, String> {
+    let (result, count) = parse_result(world, json)?;
+    return Ok(SmeltingRecipe {
+        name: name.to_string(),
+        ingredient: parse_ingredient(world, json.get("ingredient").ok_or("missing ingredient")?)?,
+        result,
+        count,
+        experience: json.get("experience").and_then(|x| x.as_f64()).unwrap_or(0.0) as f32,
+        cooking_time: json.get("cookingtime").and_then(|x| x.as_f64()).map_or(DEFAULT_COOKING_TIME, |x| x as u32),
+    });
+}
+
+// the item and count a recipe makes
+fn parse_result(world: &World, json: &Json) -> Result<(u64, u32), String> {
+    let result = json.get("result").ok_or("missing result")?;
+    // older versions name the result's item "item" and newer ones "id", smelting used to
+    // give just the id
+    let id = match result {
+        Json::String(id) => id.as_str(),
+        _ => result.get("id").or(result.get("item")).and_then(|x| x.as_str()).ok_or("missing result item")?,
+    };
+    let item = world.find_item(id).ok_or(format!("unknown item {}", id))?;
+    return Ok((item, result.get("count").and_then(|x| x.as_f64()).unwrap_or(1.0) as u32));
+}
+
+fn parse_shaped(world: &World, json: &Json) -> Result<RecipeKind, String> {
+    let rows = json.get("pattern").and_then(|x| x.as_array()).ok_or("missing pattern")?;
+    let rows: Vec<Vec<char>> = rows.iter().map(|x| x.as_str().map(|x| x.chars().collect()).ok_or("pattern rows must be strings")).collect::<Result<_, _>>()?;
+    let width = rows.first().map_or(0, |x| x.len());
+    if rows.is_empty() || rows.len() > 3 || width == 0 || width > 3 || rows.iter().any(|x| x.len() != width) {
+        return Err("the pattern must be 1 to 3 rows of the same 1 to 3 characters".to_string());
+    }
+    let key = json.get("key").and_then(|x| x.as_object()).ok_or("missing key")?;
+    let mut ingredients = HashMap::new();
+    for (symbol, ingredient) in key.iter() {
+        let mut chars = symbol.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return Err(format!("key {} must be a single character", symbol));
+        };
+        ingredients.insert(c, parse_ingredient(world, ingredient)?);
+    }
+    // empty rows and columns at the edges are cut off so the pattern can go anywhere in the grid
+    let used_row = |y: usize| rows[y].iter().any(|&c| c != ' ');
+    let used_column = |x: usize| rows.iter().any(|row| row[x] != ' ');
+    let ys: Vec<usize> = (0..rows.len()).filter(|&y| used_row(y)).collect();
+    let xs: Vec<usize> = (0..width).filter(|&x| used_column(x)).collect();
+    let (Some(&top), Some(&bottom), Some(&left), Some(&right)) = (ys.first(), ys.last(), xs.first(), xs.last()) else {
+        return Err("the pattern is empty".to_string());
+    };
+    let mut pattern = Vec::new();
+    for row in rows[top..=bottom].iter() {
+        for &c in row[left..=right].iter() {
+            pattern.push(match c {
+                ' ' => None,
+                c => Some(ingredients.get(&c).cloned().ok_or(format!("{} is not in the key", c))?),
+            });
+        }
+    }
+    return Ok(RecipeKind::Shaped {
+        width: right - left + 1,
+        height: bottom - top + 1,
+        pattern,
+    });
+}
+
+// an item, a tag, or a list of either that all work in the same spot
+fn parse_ingredient(world: &World, json: &Json) -> Result<Ingredient, String> {
+    let mut items = Vec::new();
+    match json {
+        Json::Array(options) => {
+            for option in options.iter() {
+                items.extend(parse_ingredient(world, option)?.items);
+            }
+        }
+        // newer versions write them as just the id, with tags starting with #
+        Json::String(id) => match id.strip_prefix('#') {
+            Some(tag) => items.extend(tag_items(world, tag)?),
+            None => items.push(world.find_item(id).ok_or(format!("unknown item {}", id))?),
+        },
+        Json::Object(_) => {
+            if let Some(id) = json.get("item").and_then(|x| x.as_str()) {
+                items.push(world.find_item(id).ok_or(format!("unknown item {}", id))?);
+            } else if let Some(tag) = json.get("tag").and_then(|x| x.as_str()) {
+                items.extend(tag_items(world, tag)?);
+            } else {
+                return Err("an ingredient needs an item or a tag".to_string());
+            }
+        }
+        _ => return Err("invalid ingredient".to_string()),
+    }
+    if items.is_empty() {
+        return Err("an ingredient matches no items".to_string());
+    }
+    return Ok(Ingredient { items });
+}
+
+// every item in a tag, which has to be loaded already
+pub fn tag_items(world: &World, tag: &str) -> Result<Vec<u64>, String> {
+    return world.item_tags.get(&namespaced(tag)).cloned().ok_or(format!("unknown item tag {}", tag));
+}
+
+// ids without a namespace are in minecraft's
+pub fn namespaced(id: &str) -> String {
+    if id.contains(':') {
+        return id.to_string();
+    }
+    return format!("minecraft:{}", id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    // items 1 to 3 are oak planks, birch planks and a stick, with both planks tagged
+    fn world() -> World {
+        let mut world = testing::world(&[], &["oak planks", "birch planks", "stick"]);
+        world.item_tags.insert("minecraft:planks".to_string(), vec![1, 2]);
+        return world;
+    }
+
+    fn recipe(world: &World, text: &str) -> Recipe {
+        return parse_recipe(world, "test", &json::parse(text).unwrap()).unwrap().unwrap();
+    }
+
+    fn grid(world: &World, width: usize, items: &[u64]) -> CraftingGrid {
+        let mut grid = CraftingGrid::new(width, width);
+        for (i, &item) in items.iter().enumerate() {
+            if item != 0 {
+                grid.inventory.slots[i] = Some(world.item_stack(item, 1));
+            }
+        }
+        return grid;
+    }
+
+    #[test]
+    fn shaped_recipes_match_anywhere_and_mirrored() {
+        let world = world();
+        let recipe = recipe(&world, r##"{
+            "type": "minecraft:crafting_shaped",
+            "pattern": ["PP ", "P  ", "   "],
+            "key": {"P": {"tag": "minecraft:planks"}},
+            "result": {"item": "minecraft:stick", "count": 4}
+        }"##);
+        assert_eq!(recipe.kind, RecipeKind::Shaped { width: 2, height: 2, pattern: vec![
+            Some(Ingredient { items: vec![1, 2] }), Some(Ingredient { items: vec![1, 2] }),
+            Some(Ingredient { items: vec![1, 2] }), None,
+        ] });
+        assert!(recipe.matches(&grid(&world, 3, &[0, 0, 0, 0, 1, 2, 0, 1, 0])));
+        assert!(recipe.matches(&grid(&world, 2, &[2, 1, 0, 1])));
+        assert!(!recipe.matches(&grid(&world, 2, &[1, 1, 0, 0])));
+        assert!(!recipe.matches(&grid(&world, 2, &[1, 1, 3, 0])));
+    }
+
+    #[test]
+    fn shapeless_recipes_match_in_any_order() {
+        let world = world();
+        let recipe = recipe(&world, r##"{
+            "type": "crafting_shapeless",
+            "ingredients": ["#planks", [{"item": "stick"}, {"item": "birch_planks"}]],
+            "result": {"id": "stick"}
+        }"##);
+        assert!(recipe.matches(&grid(&world, 2, &[0, 3, 1, 0])));
+        // the birch planks have to go to the list for the oak planks to fit the tag
+        assert!(recipe.matches(&grid(&world, 2, &[2, 1, 0, 0])));
+        assert!(!recipe.matches(&grid(&world, 2, &[3, 3, 0, 0])));
+        assert!(!recipe.matches(&grid(&world, 2, &[1, 0, 0, 0])));
+    }
+
+    #[test]
+    fn crafting_uses_up_one_of_each_ingredient() {
+        let mut world = world();
+        world.recipes.add(recipe(&world, r##"{
+            "type": "minecraft:crafting_shapeless",
+            "ingredients": [{"item": "minecraft:oak_planks"}],
+            "result": {"item": "minecraft:stick", "count": 2}
+        }"##));
+        let mut grid = grid(&world, 2, &[0, 1, 0, 0]);
+        grid.inventory.slots[1].as_mut().unwrap().count = 2;
+        let book = std::mem::take(&mut world.recipes);
+        assert_eq!(book.for_result(3).len(), 1);
+        assert_eq!(grid.craft(&book, &world).map(|x| (x.item, x.count)), Some((3, 2)));
+        assert_eq!(grid.get(1, 0).unwrap().count, 1);
+        grid.craft(&book, &world);
+        assert!(grid.craft(&book, &world).is_none());
+    }
+
+    #[test]
+    fn taking_the_result_out_of_a_menu_crafts() {
+        let mut world = world();
+        world.recipes.add(recipe(&world, r##"{
+            "type": "minecraft:crafting_shapeless",
+            "ingredients": [{"item": "minecraft:oak_planks"}],
+            "result": {"item": "minecraft:stick", "count": 2}
+        }"##));
+        let mut menu = inventory_menu();
+        menu.crafting.as_mut().unwrap().inventory.slots[3] = Some(world.item_stack(1, 3));
+        let mut player = Inventory::new(crate::inventory::PLAYER_INVENTORY_SIZE);
+        let result = 4;
+        let press = |menu: &mut Menu, player: &mut Inventory, world: &mut World, right: bool, shift: bool| {
+            let took = menu.with_inventories(player, world, |menu, inventories, world| menu.press(Some(result), right, shift, inventories, world));
+            if took {
+                menu.crafted(world);
+            }
+            return took;
+        };
+
+        // right clicking still takes all of it
+        assert!(press(&mut menu, &mut player, &mut world, true, false));
+        assert_eq!(menu.carried, Some(world.item_stack(3, 2)));
+        menu.carried = None;
+        assert!(press(&mut menu, &mut player, &mut world, false, true));
+        assert_eq!(player.slots.iter().flatten().map(|x| (x.item, x.count)).collect::<Vec<_>>(), vec![(3, 2)]);
+        assert_eq!(menu.close(), vec![world.item_stack(1, 1)]);
+    }
+}
@@ -0,0 +1,260 @@
+use std::any::Any;
+
+use crate::container::*;
+use crate::inventory::Inventory;
+use crate::items::ItemStack;
+use crate::nbt::{Compound, Tag};
+use crate::player::Player;
+use crate::world::*;
+
+pub const FURNACE: &str = "furnace";
+pub const LIT_FURNACE: &str = "lit furnace";
+
+pub const INPUT_SLOT: usize = 0;
+pub const FUEL_SLOT: usize = 1;
+pub const OUTPUT_SLOT: usize = 2;
+
+// losing its fire makes the progress go back this much faster than it was made
+const COOL_DOWN_RATE: u32 = 2;
+
+// cooks the input into the output while burning the fuel, the block is swapped between
+// the furnace and the lit furnace as the fire goes out and comes back
+pub struct Furnace {
+    pub slots: Inventory,
+    pub burn_time: u32, // ticks left on the fuel that's burning
+    pub burn_duration: u32, // ticks the fuel that's burning lasted in total
+    pub cook_time: u32, // ticks the input has been cooking
+    pub cook_duration: u32, // ticks the input needs
+    pub experience: f32, // from everything smelted since the output was last taken
+}
+
+impl Furnace {
+    pub fn new() -> Self {
+        return Self {
+            slots: Inventory::new(3),
+            burn_time: 0,
+            burn_duration: 0,
+            cook_time: 0,
+            cook_duration: 0,
+            experience: 0.0,
+        }
+    }
+    pub fn create() -> Box<dyn BlockEntity> {
+        return Box::new(Self::new());
+    }
+    pub fn is_lit(&self) -> bool {
+        return self.burn_time > 0;
+    }
+    // 0 to 1 for the arrow between the input and output
+    pub fn cook_progress(&self) -> f32 {
+        if self.cook_duration == 0 {
+            return 0.0;
+        }
+        return self.cook_time as f32 / self.cook_duration as f32;
+    }
+    // 0 to 1 for the flame, how much of the fuel is left
+    pub fn burn_progress(&self) -> f32 {
+        if self.burn_duration == 0 {
+            return 0.0;
+        }
+        return self.burn_time as f32 / self.burn_duration as f32;
+    }
+    // the whole experience points gained so far, the rest is kept for next time
+    pub fn take_experience(&mut self) -> u32 {
+        let points = self.experience.floor();
+        self.experience -= points;
+        return points as u32;
+    }
+    fn fits_output(&self, result: &ItemStack) -> bool {
+        match &self.slots.slots[OUTPUT_SLOT] {
+            None => true,
+            Some(output) => output.can_merge(result) && output.count + result.count <= output.max_stack,
+        }
+    }
+}
+
+impl BlockEntity for Furnace {
+    fn tick(&mut self, pos: BlockPos, world: &mut World) {
+        if self.is_lit() {
+            self.burn_time -= 1;
+        }
+        let recipe = self.slots.slots[INPUT_SLOT].as_ref().and_then(|x| world.recipes.find_smelting(x)).cloned();
+        let result = recipe.as_ref().map(|x| world.item_stack(x.result, x.count));
+        let can_smelt = result.as_ref().is_some_and(|x| self.fits_output(x));
+
+        if self.is_lit() || self.slots.slots[INPUT_SLOT].is_some() && self.slots.slots[FUEL_SLOT].is_some() {
+            let fuel = self.slots.slots[FUEL_SLOT].as_ref().map_or(0, |x| world.items[x.item as usize].burn_time);
+            if !self.is_lit() && can_smelt && fuel > 0 {
+                self.burn_time = fuel;
+                self.burn_duration = fuel;
+                self.slots.remove(FUEL_SLOT, 1);
+            }
+            match (recipe, result) {
+                (Some(recipe), Some(result)) if self.is_lit() && can_smelt => {
+                    self.cook_duration = recipe.cooking_time;
+                    self.cook_time += 1;
+                    if self.cook_time >= self.cook_duration {
+                        self.cook_time = 0;
+                        self.slots.remove(INPUT_SLOT, 1);
+                        self.slots.place(OUTPUT_SLOT, result);
+                        self.experience += recipe.experience;
+                    }
+                }
+                _ => self.cook_time = 0,
+            }
+        } else if self.cook_time > 0 {
+            self.cook_time = self.cook_time.saturating_sub(COOL_DOWN_RATE);
+        }
+
+        // the new block makes its own furnace from this one's nbt so nothing is lost
+        let Some(id) = world.find_block(if self.is_lit() { LIT_FURNACE } else { FURNACE }) else {
+            return;
+        };
+        if world.block_at(pos.to_vec()).is_some_and(|x| x.get_id() != id) {
+            let mut nbt = NbtBlock::new();
+            self.save(&mut nbt.data, world);
+            world.place_block(pos.to_vec(), Block::new(id, nbt, world));
+        }
+    }
+    fn inventory(&self) -> Option<&Inventory> {
+        return Some(&self.slots);
+    }
+    fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        return Some(&mut self.slots);
+    }
+    // the input over the fuel with the flame between them, and an arrow to the output
+    fn menu(&self, pos: BlockPos, _world: &World) -> Option<Menu> {
+        let mut menu = Menu::new("Furnace", vec![ContainerRef::Player, ContainerRef::Block(pos)]);
+        // shift clicking from the player only reaches the slots that accept the item
+        let input = menu.add_grid(1, INPUT_SLOT, 1, 1, (55.0, 16.0), SlotKind::Smeltable);
+        menu.add_grid(1, FUEL_SLOT, 1, 1, (55.0, 52.0), SlotKind::Fuel);
+        let output = menu.add_grid(1, OUTPUT_SLOT, 1, 1, (115.0, 34.0), SlotKind::TakeOnly);
+        menu.bars.push(ProgressBar { x: 57.0, y: 36.0, width: 14.0, height: 14.0 });
+        menu.bars.push(ProgressBar { x: 80.0, y: 35.0, width: 24.0, height: 16.0 });
+        let slots = input.start..output.end;
+        menu.add_player_inventory(vec![slots]);
+        return Some(menu);
+    }
+    fn progress(&self) -> Vec<f32> {
+        return vec![self.burn_progress(), self.cook_progress()];
+    }
+    fn output_taken(&mut self, player: &mut Player) {
+        player.experience += self.take_experience();
+    }
+    fn save(&self, nbt: &mut Compound, world: &World) {
+        nbt.set("items", self.slots.save(world));
+        nbt.set("burn_time", Tag::Int(self.burn_time as i32));
+        nbt.set("burn_duration", Tag::Int(self.burn_duration as i32));
+        nbt.set("cook_time", Tag::Int(self.cook_time as i32));
+        nbt.set("cook_duration", Tag::Int(self.cook_duration as i32));
+        nbt.set("experience", Tag::Float(self.experience));
+    }
+    fn load(&mut self, nbt: &Compound, world: &World) {
+        if let Some(items) = nbt.get("items") {
+            self.slots.load(items, world);
+        }
+        self.burn_time = nbt.get_int("burn_time").unwrap_or(0).max(0) as u32;
+        self.burn_duration = nbt.get_int("burn_duration").unwrap_or(0).max(0) as u32;
+        self.cook_time = nbt.get_int("cook_time").unwrap_or(0).max(0) as u32;
+        self.cook_duration = nbt.get_int("cook_duration").unwrap_or(0).max(0) as u32;
+        self.experience = nbt.get_float("experience").unwrap_or(0.0);
+    }
+    fn as_any(&self) -> &dyn Any {
+        return self;
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        return self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crafting::{Ingredient, SmeltingRecipe};
+    use crate::testing;
+
+    // furnace blocks 1 and 2, and ore, ingot and coal items after their block items
+    fn world() -> (World, u64, u64, u64) {
+        let mut world = testing::world(&[FURNACE, LIT_FURNACE], &["ore", "ingot", "coal"]);
+        for block in [1, 2] {
+            world.blocks[block].block_entity = Some(Furnace::create);
+        }
+        world.blocks[2].light_emission = (13, 13, 13);
+        let first = world.find_item("ore").unwrap();
+        world.items[first as usize + 2].burn_time = 1600;
+        world.recipes.add_smelting(SmeltingRecipe {
+            name: "ingot".to_string(),
+            ingredient: Ingredient { items: vec![first] },
+            result: first + 1,
+            count: 1,
+            experience: 0.7,
+            cooking_time: 200,
+        });
+        return (world, first, first + 1, first + 2);
+    }
+
+    fn furnace(world: &mut World, pos: BlockPos) -> &mut Furnace {
+        return world.block_entity_as::<Furnace>(pos).unwrap();
+    }
+
+    #[test]
+    fn smelts_and_lights_up_while_burning() {
+        let (mut world, ore, ingot, coal) = world();
+        let pos = BlockPos::new(2, 2, 2);
+        world.place_block(pos.to_vec(), Block::new(1, NbtBlock::new(), &world));
+        let (input, fuel) = (world.item_stack(ore, 2), world.item_stack(coal, 1));
+        let slots = &mut furnace(&mut world, pos).slots;
+        slots.slots[INPUT_SLOT] = Some(input);
+        slots.slots[FUEL_SLOT] = Some(fuel);
+
+        world.tick();
+        assert_eq!(world.block_at(pos.to_vec()).unwrap().get_id(), 2);
+        assert_eq!(world.get_block_light(pos.to_vec()).0, 13);
+        assert!(furnace(&mut world, pos).slots.slots[FUEL_SLOT].is_none());
+
+        for _ in 0..399 {
+            world.tick();
+        }
+        let smelted = furnace(&mut world, pos);
+        assert!(smelted.slots.slots[INPUT_SLOT].is_none());
+        assert_eq!(smelted.slots.slots[OUTPUT_SLOT].as_ref().map(|x| (x.item, x.count)), Some((ingot, 2)));
+        assert_eq!(smelted.take_experience(), 1);
+        assert!((smelted.experience - 0.4).abs() < 1e-4);
+
+        // nothing left to cook, so the fuel burns out and the furnace goes dark
+        for _ in 0..1201 {
+            world.tick();
+        }
+        assert_eq!(world.block_at(pos.to_vec()).unwrap().get_id(), 1);
+        assert_eq!(world.get_block_light(pos.to_vec()).0, 0);
+        assert_eq!(furnace(&mut world, pos).slots.slots[OUTPUT_SLOT].as_ref().unwrap().count, 2);
+    }
+
+    #[test]
+    fn progress_cools_down_without_fuel() {
+        let (mut world, ore, _, coal) = world();
+        let pos = BlockPos::new(2, 2, 2);
+        world.place_block(pos.to_vec(), Block::new(1, NbtBlock::new(), &world));
+        let (input, bad_fuel, fuel) = (world.item_stack(ore, 1), world.item_stack(ore, 1), world.item_stack(coal, 1));
+        let lit = furnace(&mut world, pos);
+        lit.slots.slots[INPUT_SLOT] = Some(input);
+        lit.burn_time = 10;
+        lit.burn_duration = 10;
+        // the last tick of fuel burns out before it can cook
+        for _ in 0..10 {
+            world.tick();
+        }
+        assert_eq!(furnace(&mut world, pos).cook_time, 7);
+        assert!(!furnace(&mut world, pos).is_lit());
+        world.tick();
+        assert_eq!(furnace(&mut world, pos).cook_time, 5);
+
+        // fuel that doesn't burn doesn't light it
+        furnace(&mut world, pos).slots.slots[FUEL_SLOT] = Some(bad_fuel);
+        world.tick();
+        assert_eq!(furnace(&mut world, pos).cook_time, 0);
+        furnace(&mut world, pos).slots.slots[FUEL_SLOT] = Some(fuel);
+        world.tick();
+        assert!(furnace(&mut world, pos).is_lit());
+    }
+}
@@ -1,5 +1,5 @@
 use glfw::{Context, Glfw, GlfwReceiver, PWindow};
-pub use glfw::{WindowMode, WindowEvent as Event, Key, Action, CursorMode, MouseButton};
+pub use glfw::{WindowMode, WindowEvent as Event, Key, Action, CursorMode, MouseButton, Modifiers};
 use image::{DynamicImage, GenericImageView};
 use std::{collections::HashMap, thread::sleep, time::{Duration, Instant}};
 
@@ -95,6 +95,24 @@ pub mod files {
         Ok(contents)
     }
 
+    // the paths of the files in a directory, sorted so they always load in the same order
+    pub fn list_files(dir: &str, dev: &bool) -> Result<Vec<String>, std::io::Error> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(file_name(dir, dev)?)? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                names.push(format!("{}/{}", dir.trim_end_matches('/'), entry.file_name().to_string_lossy()));
+            }
+        }
+        names.sort();
+        return Ok(names);
+    }
+
+    // saves go next to the executable even in dev mode so they stay out of src
+    pub fn save_path(name: &str) -> Result<String, std::io::Error> {
+        return file_name(name, &false);
+    }
+
     pub fn load_texture(filename: &str, dev: &bool) -> Result<DynamicImage, std::io::Error> {
         let path = file_name(filename, dev)?;
         let img = image::open(path).expect("Failed to load texture");
@@ -110,6 +128,67 @@ pub mod draw {
     
     use super::Vec3;
 
+    // position, uv, ambient occlusion, sky light, block light
+    pub const VERTEX_ATTRIBUTES: [i32; 5] = [3, 2, 1, 1, 3];
+    // position, rgba
+    pub const COLOR_VERTEX_ATTRIBUTES: [i32; 2] = [3, 4];
+
+    // two triangles for shapes that only have a color, like the sky
+    pub fn colored_square(vec: &mut Vec<f32>, tl: Vec3, tr: Vec3, br: Vec3, bl: Vec3, color: (f32, f32, f32, f32)) {
+        for p in [tl, tr, br, bl, tl, br] {
+            vec.extend_from_slice(&[p.x, p.y, -p.z, color.0, color.1, color.2, color.3]);
+        }
+    }
+
+    // the 12 edges of a box as pairs of points for drawing lines
+    pub fn box_lines(vec: &mut Vec<f32>, min: Vec3, max: Vec3, color: (f32, f32, f32, f32)) {
+        let corner = |x: bool, y: bool, z: bool| Vec3::new(
+            if x { max.x } else { min.x },
+            if y { max.y } else { min.y },
+            if z { max.z } else { min.z },
+        );
+        for a in [false, true] {
+            for b in [false, true] {
+                for (p1, p2) in [
+                    (corner(false, a, b), corner(true, a, b)),
+                    (corner(a, false, b), corner(a, true, b)),
+                    (corner(a, b, false), corner(a, b, true)),
+                ] {
+                    for p in [p1, p2] {
+                        vec.extend_from_slice(&[p.x, p.y, -p.z, color.0, color.1, color.2, color.3]);
+                    }
+                }
+            }
+        }
+    }
+
+    // per vertex lighting
+    #[derive(Clone, Copy)]
+    pub struct Shade {
+        pub ao: f32,
+        pub sky: f32, // 0 to 1
+        pub block: (f32, f32, f32), // rgb, 0 to 1
+    }
+
+    impl Shade {
+        pub fn new() -> Self {
+            return Self {
+                ao: 1.0,
+                sky: 1.0,
+                block: (0.0, 0.0, 0.0),
+            }
+        }
+    }
+
+    // a square with its own uvs and lighting, everything is in the order of the corners:
+    // top left, top right, bottom right, bottom left
+    #[derive(Clone, Copy)]
+    pub struct Quad {
+        pub corners: [Vec3; 4],
+        pub uvs: [(f32, f32); 4], // 0 to 1 inside the texture
+        pub shades: [Shade; 4],
+    }
+
     pub struct Triangle {
         pub p1: Vec3,
         pub p2: Vec3,
@@ -118,14 +197,25 @@ pub mod draw {
         pub t1: (f32, f32),
         pub t2: (f32, f32),
         pub t3: (f32, f32),
+
+        pub s1: Shade,
+        pub s2: Shade,
+        pub s3: Shade,
     }
 
     impl Triangle {
         pub fn new(p1: Vec3, p2: Vec3, p3: Vec3, texture_id: &TextureLocation, t1: TextureMapping, t2: TextureMapping, t3: TextureMapping) -> Self {
             Self {
                 p1, p2, p3, t1: t1.get(*texture_id), t2: t2.get(*texture_id), t3: t3.get(*texture_id),
+                s1: Shade::new(), s2: Shade::new(), s3: Shade::new(),
             }
         } 
+        pub fn shaded(mut self, s1: Shade, s2: Shade, s3: Shade) -> Self {
+            self.s1 = s1;
+            self.s2 = s2;
+            self.s3 = s3;
+            return self;
+        }
         pub fn create(vec: &mut Vec<f32>, p1: Vec3, p2: Vec3, p3: Vec3, texture_id: &TextureLocation, t1: TextureMapping, t2: TextureMapping, t3: TextureMapping) {
             Self::new(p1, p2, p3, texture_id, t1, t2, t3).to_points(vec);
         }
@@ -137,22 +227,51 @@ pub mod draw {
             vec.push(Self::new(tl, tr, br, texture_id, TextureMapping::TopLeft, TextureMapping::TopRight, TextureMapping::BottomRight));
             vec.push(Self::new(bl, tl, br, texture_id, TextureMapping::BottomLeft, TextureMapping::TopLeft, TextureMapping::BottomRight));
         }
+        pub fn square_mapped(vec: &mut Vec<Triangle>, quad: Quad, texture_id: &TextureLocation) {
+            let [tl, tr, br, bl] = quad.corners;
+            let [tl_uv, tr_uv, br_uv, bl_uv] = quad.uvs.map(|(u, v)| TextureMapping::Manual(u, v));
+            let [tl_s, tr_s, br_s, bl_s] = quad.shades;
+            // split along the diagonal with the darker corners, otherwise the
+            // ambient occlusion gradient changes with the direction of the face
+            if tl_s.ao + br_s.ao > tr_s.ao + bl_s.ao {
+                vec.push(Self::new(tr, br, bl, texture_id, tr_uv, br_uv, bl_uv).shaded(tr_s, br_s, bl_s));
+                vec.push(Self::new(tl, tr, bl, texture_id, tl_uv, tr_uv, bl_uv).shaded(tl_s, tr_s, bl_s));
+            } else {
+                vec.push(Self::new(tl, tr, br, texture_id, tl_uv, tr_uv, br_uv).shaded(tl_s, tr_s, br_s));
+                vec.push(Self::new(bl, tl, br, texture_id, bl_uv, tl_uv, br_uv).shaded(bl_s, tl_s, br_s));
+            }
+        }
         pub fn to_points(&self, vec: &mut Vec<f32>) {
             vec.push(self.p1.x);
             vec.push(self.p1.y);
             vec.push(-self.p1.z);
             vec.push(self.t1.0);
             vec.push(self.t1.1);
+            vec.push(self.s1.ao);
+            vec.push(self.s1.sky);
+            vec.push(self.s1.block.0);
+            vec.push(self.s1.block.1);
+            vec.push(self.s1.block.2);
             vec.push(self.p2.x);
             vec.push(self.p2.y);
             vec.push(-self.p2.z);
             vec.push(self.t2.0);
             vec.push(self.t2.1);
+            vec.push(self.s2.ao);
+            vec.push(self.s2.sky);
+            vec.push(self.s2.block.0);
+            vec.push(self.s2.block.1);
+            vec.push(self.s2.block.2);
             vec.push(self.p3.x);
             vec.push(self.p3.y);
             vec.push(-self.p3.z);
             vec.push(self.t3.0);
             vec.push(self.t3.1);
+            vec.push(self.s3.ao);
+            vec.push(self.s3.sky);
+            vec.push(self.s3.block.0);
+            vec.push(self.s3.block.1);
+            vec.push(self.s3.block.2);
         }
     }
     trait ToPoints {
@@ -184,7 +303,7 @@ impl Unwrap<u32, u32> for Option<(u32, u32)> {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct TextureLocation {
     tl: (f32, f32),
     tr: (f32, f32),
@@ -192,6 +311,7 @@ pub struct TextureLocation {
     br: (f32, f32),
 }
 
+#[derive(Clone, Copy)]
 pub enum TextureMapping {
     TopLeft,
     TopRight,
@@ -398,6 +518,36 @@ impl Shaders {
             }
         }
     }
+    pub fn set_uniform_float(&self, name: &str, value: f32) {
+        unsafe {
+            let location = gl::GetUniformLocation(self.program, std::ffi::CString::new(name).unwrap().as_ptr() as *const i8);
+            if location != -1 {
+                gl::Uniform1f(location, value);
+            } else {
+                eprintln!("Uniform {} not found in shader program", name);
+            }
+        }
+    }
+    pub fn set_uniform_int(&self, name: &str, value: i32) {
+        unsafe {
+            let location = gl::GetUniformLocation(self.program, std::ffi::CString::new(name).unwrap().as_ptr() as *const i8);
+            if location != -1 {
+                gl::Uniform1i(location, value);
+            } else {
+                eprintln!("Uniform {} not found in shader program", name);
+            }
+        }
+    }
+    pub fn set_uniform_vec3(&self, name: &str, value: &Vec3) {
+        unsafe {
+            let location = gl::GetUniformLocation(self.program, std::ffi::CString::new(name).unwrap().as_ptr() as *const i8);
+            if location != -1 {
+                gl::Uniform3f(location, value.x, value.y, value.z);
+            } else {
+                eprintln!("Uniform {} not found in shader program", name);
+            }
+        }
+    }
     pub fn reg_texture<T: TextureName>(&mut self, name: T, img: DynamicImage) { 
         self.uncompiled_textures.insert(name.get_texture_name(), img);
     }
@@ -496,9 +646,67 @@ impl Shaders {
     }
 }
 
+const SKY_FAR: f32 = 1000.0;
+
+#[derive(Clone, Copy)]
+pub enum FogMode {
+    None,
+    Linear(f32, f32), // start and end distance
+    Exponential(f32), // density
+}
+
+#[derive(Clone, Copy)]
+pub struct Fog {
+    pub color: Vec3,
+    pub mode: FogMode,
+}
+
+impl Fog {
+    pub fn none() -> Self {
+        return Self {
+            color: Vec3::new(0.0, 0.0, 0.0),
+            mode: FogMode::None,
+        }
+    }
+    // fades out the last part of the view so blocks don't pop in at the far plane
+    pub fn distance(color: Vec3, far: f32) -> Self {
+        return Self {
+            color,
+            mode: FogMode::Linear(far * 0.7, far),
+        }
+    }
+    pub fn water() -> Self {
+        return Self {
+            color: Vec3::new(0.05, 0.15, 0.5),
+            mode: FogMode::Exponential(0.08),
+        }
+    }
+    pub fn lava() -> Self {
+        return Self {
+            color: Vec3::new(0.6, 0.1, 0.0),
+            mode: FogMode::Exponential(1.5),
+        }
+    }
+    pub fn set_uniforms(&self, shaders: &Shaders) {
+        let (mode, start, end, density) = match self.mode {
+            FogMode::None => (0, 0.0, 0.0, 0.0),
+            FogMode::Linear(start, end) => (1, start, end, 0.0),
+            FogMode::Exponential(density) => (2, 0.0, 0.0, density),
+        };
+        shaders.set_uniform_int("u_FogMode", mode);
+        shaders.set_uniform_vec3("u_FogColor", &self.color);
+        shaders.set_uniform_float("u_FogStart", start);
+        shaders.set_uniform_float("u_FogEnd", end);
+        shaders.set_uniform_float("u_FogDensity", density);
+    }
+}
+
 pub struct Camera {
     pub pos: Vec3,
     pub rotation: Vec2,
+    pub fov: f32, // vertical, in degrees
+    pub near: f32,
+    pub far: f32,
 }
 
 impl Camera {
@@ -506,18 +714,32 @@ impl Camera {
        return Self {
            pos: Vec3::new(0.0, 0.0, 0.0),
            rotation: Vec2::new(0.0, 0.0),
+           fov: 45.0,
+           near: 0.1,
+           far: 100.0,
        } 
     }
+    pub fn to_projection(&self, aspect_ratio: f32) -> Mat4 {
+        return glm::perspective(aspect_ratio, self.fov.to_radians(), self.near, self.far);
+    }
     pub fn to_matrix(&self) -> Mat4 {
-        let mut view = glm::identity();
         let mut pos = self.pos.clone();
         pos.z *= -1.0;
         
+        return glm::translate(&self.to_rotation_matrix(), &-pos);
+    }
+    // unit vector in world space the camera is looking along
+    pub fn forward(&self) -> Vec3 {
+        let (yaw, pitch) = (self.rotation.x, self.rotation.y);
+        return Vec3::new(pitch.cos() * yaw.sin(), -pitch.sin(), pitch.cos() * yaw.cos());
+    }
+    // only the rotation of the camera, for things that stay around it like the sky
+    pub fn to_rotation_matrix(&self) -> Mat4 {
+        let mut view = glm::identity();
+
         view = glm::rotate_x(&view, self.rotation.y);
 
         view = glm::rotate_y(&view, self.rotation.x);
-        
-        view = glm::translate(&view, &-pos);
 
         return view;
     }
@@ -531,17 +753,28 @@ pub struct Window<Data> {
     pub min_size: Option<(u32, u32)>,
     pub max_size: Option<(u32, u32)>,
     on_event: fn(&mut Self, Event) -> (),
-    render: fn(&mut Self) -> (),
+    render: fn(&mut Self, f32) -> (),
     pub shaders: Shaders,
+    pub sky_shaders: Shaders,
+    pub fog: Fog,
     pub data: Data,
     pub fps: f64,
     pub deltatime: f64,
     pub frame_count: u64,
     pub max_fps: Option<f64>,
+    pub tick_rate: f64, // updates per second
+    pub max_ticks_per_frame: u32,
+    pub tick_count: u64,
+    tick_time: f64, // time not yet updated, in seconds
     pub camera: Camera,
 }
 
 impl<Data> Window<Data> {
+    pub fn set_clear_color(&mut self, color: Vec3) {
+        unsafe {
+            gl::ClearColor(color.x, color.y, color.z, 1.0);
+        }
+    }
     pub fn close(&mut self) {
         self.window_handler.set_should_close(true);
     }
@@ -593,15 +826,21 @@ impl<Data> Window<Data> {
             event_handler: Box::new(events),
             update: |_window| {},
             on_event: |_window, _event| {},
-            render: |_window| {},
+            render: |_window, _alpha| {},
             min_size: None,
             max_size: None,
             shaders: Shaders::new(),
+            sky_shaders: Shaders::new(),
+            fog: Fog::none(),
             data,
             fps: 0.0,
             deltatime: 0.0,
             frame_count: 0,
             max_fps: None,
+            tick_rate: 20.0,
+            max_ticks_per_frame: 10,
+            tick_count: 0,
+            tick_time: 0.0,
             camera: Camera::new(),
         }
     }
@@ -632,12 +871,21 @@ impl<Data> Window<Data> {
     pub fn set_max_fps(&mut self, fps: f64) {
         self.max_fps = Some(fps);
     }
+    pub fn set_tick_rate(&mut self, ticks: f64) {
+        self.tick_rate = ticks;
+    }
+    // how many updates can run in one frame to catch up after a slow frame, the rest are skipped
+    pub fn set_max_ticks_per_frame(&mut self, ticks: u32) {
+        self.max_ticks_per_frame = ticks;
+    }
     pub fn set_on_event(&mut self, func: fn(&mut Self, Event) -> ()) {
         self.on_event = func;
     }
-    pub fn set_render(&mut self, func: fn(&mut Self) -> ()) {
+    // render gets how far it is between the last update and the next one, from 0 to 1
+    pub fn set_render(&mut self, func: fn(&mut Self, f32) -> ()) {
         self.render = func;
     }
+    // update runs tick_rate times a second however fast frames are drawn
     pub fn set_update(&mut self, func: fn(&mut Self) -> ()) {
         self.update = func;
     }
@@ -659,14 +907,28 @@ impl<Data> Window<Data> {
                 (self.on_event)(self, event);
             }
 
-            (self.update)(self);
+            // Fixed updates
+            let tick_length = 1.0 / self.tick_rate;
+            self.tick_time += self.deltatime;
+            let mut ticks = 0;
+            while self.tick_time >= tick_length {
+                if ticks == self.max_ticks_per_frame {
+                    self.tick_time %= tick_length;
+                    break;
+                }
+                (self.update)(self);
+                self.tick_time -= tick_length;
+                self.tick_count = self.tick_count.wrapping_add(1);
+                ticks += 1;
+            }
+            let alpha = (self.tick_time / tick_length) as f32;
 
             // Render loop
             unsafe {
-                gl::Clear(gl::COLOR_BUFFER_BIT);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
             }
 
-            (self.render)(self);
+            (self.render)(self, alpha);
 
             self.window_handler.swap_buffers();
 
@@ -687,27 +949,102 @@ impl<Data> Window<Data> {
         }
     }
     pub fn render_triangles(&self, vec: &Vec<f32>) {
+        self.draw_triangles(vec, 0.5);
+    }
+    // drawn after the opaque pass, expects the triangles to already be sorted back to front
+    pub fn render_translucent_triangles(&self, vec: &Vec<f32>) {
+        unsafe {
+            gl::DepthMask(gl::FALSE);
+        }
+        self.draw_triangles(vec, 0.01);
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+        }
+    }
+    fn draw_triangles(&self, vec: &Vec<f32>, alpha_cutoff: f32) {
         unsafe {
-            let (width, height) = self.get_resolution();
-            let aspect_ratio = width as f32 / height as f32;
-
-            // Create perspective matrix
-            let fov_y = 45.0_f32.to_radians();
-            let near = 0.1;
-            let far = 100.0;
-            let projection_matrix = glm::perspective(aspect_ratio, fov_y, near, far);
-
             gl::ActiveTexture(gl::TEXTURE0); // Activate texture unit 0
             gl::BindTexture(gl::TEXTURE_2D, self.shaders.texture_atlas); // Bind the texture
 
             self.shaders.use_program();
-            self.shaders.set_uniform_matrix("u_ProjectionMatrix", &projection_matrix);
+            self.shaders.set_uniform_matrix("u_ProjectionMatrix", &self.camera.to_projection(self.aspect_ratio()));
             self.shaders.set_uniform_matrix("u_CameraMatrix", &self.camera.to_matrix());
+            self.shaders.set_uniform_float("u_AlphaCutoff", alpha_cutoff);
+            self.fog.set_uniforms(&self.shaders);
 
             // Set the texture uniform
             let texture_uniform_location = gl::GetUniformLocation(self.shaders.program, "textureSampler\0".as_ptr() as *const i8);
             gl::Uniform1i(texture_uniform_location, 0);
 
+            self.draw_arrays(vec, &draw::VERTEX_ATTRIBUTES, gl::TRIANGLES);
+        }    
+    }
+    // draws the sky shapes around the camera without moving them, then the clouds in the world
+    pub fn render_sky(&self, sky: &Vec<f32>, clouds: &Vec<f32>) {
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+
+            // the sky is always drawn, however short the far plane is
+            let sky_projection = glm::perspective(self.aspect_ratio(), self.camera.fov.to_radians(), self.camera.near, SKY_FAR);
+            self.sky_shaders.use_program();
+            self.sky_shaders.set_uniform_matrix("u_ProjectionMatrix", &sky_projection);
+            self.sky_shaders.set_uniform_matrix("u_CameraMatrix", &self.camera.to_rotation_matrix());
+            Fog::none().set_uniforms(&self.sky_shaders);
+            self.draw_arrays(sky, &draw::COLOR_VERTEX_ATTRIBUTES, gl::TRIANGLES);
+
+            gl::Enable(gl::DEPTH_TEST);
+            self.sky_shaders.set_uniform_matrix("u_ProjectionMatrix", &self.camera.to_projection(self.aspect_ratio()));
+            self.sky_shaders.set_uniform_matrix("u_CameraMatrix", &self.camera.to_matrix());
+            self.fog.set_uniforms(&self.sky_shaders);
+            self.draw_arrays(clouds, &draw::COLOR_VERTEX_ATTRIBUTES, gl::TRIANGLES);
+
+            gl::Enable(gl::CULL_FACE);
+        }
+    }
+    // draws pairs of colored vertices as lines in the world
+    pub fn render_lines(&self, lines: &Vec<f32>) {
+        self.sky_shaders.use_program();
+        self.sky_shaders.set_uniform_matrix("u_ProjectionMatrix", &self.camera.to_projection(self.aspect_ratio()));
+        self.sky_shaders.set_uniform_matrix("u_CameraMatrix", &self.camera.to_matrix());
+        self.fog.set_uniforms(&self.sky_shaders);
+        self.draw_arrays(lines, &draw::COLOR_VERTEX_ATTRIBUTES, gl::LINES);
+    }
+    // draws colored and then textured vertices in screen pixels from the top left over everything else
+    pub fn render_hud(&self, colored: &Vec<f32>, textured: &Vec<f32>) {
+        let (width, height) = self.get_resolution();
+        let projection = glm::ortho(0.0, width as f32, height as f32, 0.0, -1.0, 1.0);
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+
+            self.sky_shaders.use_program();
+            self.sky_shaders.set_uniform_matrix("u_ProjectionMatrix", &projection);
+            self.sky_shaders.set_uniform_matrix("u_CameraMatrix", &glm::identity());
+            Fog::none().set_uniforms(&self.sky_shaders);
+            self.draw_arrays(colored, &draw::COLOR_VERTEX_ATTRIBUTES, gl::TRIANGLES);
+
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, self.shaders.texture_atlas);
+            self.shaders.use_program();
+            self.shaders.set_uniform_matrix("u_ProjectionMatrix", &projection);
+            self.shaders.set_uniform_matrix("u_CameraMatrix", &glm::identity());
+            self.shaders.set_uniform_float("u_AlphaCutoff", 0.5);
+            Fog::none().set_uniforms(&self.shaders);
+            self.draw_arrays(textured, &draw::VERTEX_ATTRIBUTES, gl::TRIANGLES);
+
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Enable(gl::CULL_FACE);
+        }
+    }
+    fn aspect_ratio(&self) -> f32 {
+        let (width, height) = self.get_resolution();
+        return width as f32 / height as f32;
+    }
+    // uploads the vertices and draws them with the program in use, attributes are the
+    // number of floats in each vertex attribute in the order of their locations
+    fn draw_arrays(&self, vec: &Vec<f32>, attributes: &[i32], mode: u32) {
+        unsafe {
             // Vertex Array Object (VAO) and Vertex Buffer Object (VBO) setup
             let mut vao: u32 = 0;
             let mut vbo: u32 = 0; 
@@ -725,35 +1062,33 @@ impl<Data> Window<Data> {
                 gl::STATIC_DRAW,
                 );
 
-            // Set up position attribute
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, 5 * std::mem::size_of::<f32>() as i32, std::ptr::null());
-            gl::EnableVertexAttribArray(0);
-
-            // Set up texture coordinate attribute
-            gl::VertexAttribPointer(
-                1,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                5 * std::mem::size_of::<f32>() as i32,
-                (3 * std::mem::size_of::<f32>()) as *const _,
-                );
-            gl::EnableVertexAttribArray(1);
+            let vertex_size: i32 = attributes.iter().sum();
+            let stride = vertex_size * std::mem::size_of::<f32>() as i32;
+            let mut offset = 0;
+            for (location, size) in attributes.iter().enumerate() {
+                gl::VertexAttribPointer(
+                    location as u32,
+                    *size,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    (offset as usize * std::mem::size_of::<f32>()) as *const _,
+                    );
+                gl::EnableVertexAttribArray(location as u32);
+                offset += size;
+            }
 
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
             gl::BindVertexArray(0);
 
-            // Draw the square using two triangles
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT); // Clear buffers
-            self.shaders.use_program(); // Ensure shader program is used
             gl::BindVertexArray(vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, (vec.len() / 5) as i32);
+            gl::DrawArrays(mode, 0, vec.len() as i32 / vertex_size);
 
             // Clean up
             gl::BindVertexArray(0);
             gl::DeleteVertexArrays(1, &mut vao);
             gl::DeleteBuffers(1, &mut vbo);
-        }    
+        }
     }
 }
 
@@ -0,0 +1,225 @@
+use crate::container::*;
+use crate::graphics::{*, draw::*};
+use crate::inventory::HOTBAR_SIZE;
+use crate::items::ItemStack;
+use crate::player::*;
+use crate::world::*;
+
+const SCALE: f32 = 2.0; // screen pixels per hud pixel
+const CROSSHAIR_SIZE: f32 = 9.0;
+const CROSSHAIR_WIDTH: f32 = 1.0;
+const HEART_SIZE: f32 = 8.0;
+const HEART_SPACING: f32 = 1.0;
+const HEALTH_BOTTOM: f32 = 24.0; // above the hotbar
+const SLOT_SIZE: f32 = 20.0;
+const ICON_SIZE: f32 = 16.0;
+const HOTBAR_BOTTOM: f32 = 2.0;
+const TITLE_POS: (f32, f32) = (8.0, 5.0); // in menu pixels from the menu's top left
+const LETTER_WIDTH: f32 = 4.0; // 3 pixels and a gap
+
+const CROSSHAIR_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 0.8);
+const HEART_COLOR: (f32, f32, f32, f32) = (0.85, 0.1, 0.1, 1.0);
+const EMPTY_HEART_COLOR: (f32, f32, f32, f32) = (0.15, 0.05, 0.05, 0.8);
+const SLOT_COLOR: (f32, f32, f32, f32) = (0.1, 0.1, 0.1, 0.6);
+const SELECTED_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 0.9);
+const COUNT_COLOR: (f32, f32, f32, f32) = (0.3, 0.9, 0.3, 1.0);
+const SHADE_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.5); // over the world behind a menu
+const PANEL_COLOR: (f32, f32, f32, f32) = (0.78, 0.78, 0.78, 1.0);
+const MENU_SLOT_COLOR: (f32, f32, f32, f32) = (0.55, 0.55, 0.55, 1.0);
+const HOVER_COLOR: (f32, f32, f32, f32) = (0.7, 0.7, 0.7, 1.0);
+const BAR_COLOR: (f32, f32, f32, f32) = (0.45, 0.45, 0.45, 1.0);
+const BAR_FILL_COLOR: (f32, f32, f32, f32) = (1.0, 0.6, 0.1, 1.0);
+const TITLE_COLOR: (f32, f32, f32, f32) = (0.25, 0.25, 0.25, 1.0);
+
+// capital letters 3 pixels wide and 5 high, each row is 3 bits from the top down
+const FONT: [u16; 26] = [
+    0b010_101_111_101_101, 0b110_101_110_101_110, 0b011_100_100_100_011, 0b110_101_101_101_110,
+    0b111_100_110_100_111, 0b111_100_110_100_100, 0b011_100_101_101_011, 0b101_101_111_101_101,
+    0b111_010_010_010_111, 0b001_001_001_101_010, 0b101_101_110_101_101, 0b100_100_100_100_111,
+    0b101_111_111_101_101, 0b110_101_101_101_101, 0b010_101_101_101_010, 0b110_101_110_100_100,
+    0b010_101_101_110_011, 0b110_101_110_101_101, 0b011_100_010_001_110, 0b111_010_010_010_010,
+    0b101_101_101_101_111, 0b101_101_101_101_010, 0b101_101_111_111_101, 0b101_101_010_101_101,
+    0b101_101_010_010_010, 0b111_001_010_100_111,
+];
+
+// vertices in screen pixels from the top left, the colored shapes and the textured item icons
+pub fn build<T>(player: &Player, world: &World, window: &Window<T>, width: f32, height: f32) -> (Vec<f32>, Vec<f32>) {
+    let mut colored = Vec::new();
+    let mut icons = Vec::new();
+    if player.menu.is_some() {
+        menu(&mut colored, &mut icons, player, world, window, width, height);
+        return (colored, icons);
+    }
+    let abilities = player.game_mode.abilities();
+    if abilities.shows_crosshair {
+        crosshair(&mut colored, width, height);
+    }
+    if abilities.shows_health {
+        health(&mut colored, player.health, width, height);
+    }
+    if abilities.shows_hotbar {
+        hotbar(&mut colored, &mut icons, player, world, window, width, height);
+    }
+    return (colored, icons);
+}
+
+fn crosshair(vec: &mut Vec<f32>, width: f32, height: f32) {
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let half_size = CROSSHAIR_SIZE * SCALE / 2.0;
+    let half_width = CROSSHAIR_WIDTH * SCALE / 2.0;
+    rect(vec, cx - half_size, cy - half_width, cx + half_size, cy + half_width, CROSSHAIR_COLOR);
+    rect(vec, cx - half_width, cy - half_size, cx + half_width, cy - half_width, CROSSHAIR_COLOR);
+    rect(vec, cx - half_width, cy + half_width, cx + half_width, cy + half_size, CROSSHAIR_COLOR);
+}
+
+// a heart for every 2 health, the last one is cut in half for odd health
+fn health(vec: &mut Vec<f32>, health: f32, width: f32, height: f32) {
+    let hearts = (MAX_HEALTH / 2.0).ceil() as usize;
+    let size = HEART_SIZE * SCALE;
+    let step = (HEART_SIZE + HEART_SPACING) * SCALE;
+    let left = width / 2.0 - step * hearts as f32 / 2.0;
+    let bottom = height - HEALTH_BOTTOM * SCALE;
+    let health = health.max(0.0).ceil();
+    for i in 0..hearts {
+        let x = left + step * i as f32;
+        let filled = ((health - i as f32 * 2.0) / 2.0).clamp(0.0, 1.0);
+        rect(vec, x, bottom - size, x + size, bottom, EMPTY_HEART_COLOR);
+        if filled > 0.0 {
+            rect(vec, x, bottom - size, x + size * filled, bottom, HEART_COLOR);
+        }
+    }
+}
+
+fn hotbar<T>(colored: &mut Vec<f32>, icons: &mut Vec<f32>, player: &Player, world: &World, window: &Window<T>, width: f32, height: f32) {
+    let size = SLOT_SIZE * SCALE;
+    let left = width / 2.0 - size * HOTBAR_SIZE as f32 / 2.0;
+    let top = height - (HOTBAR_BOTTOM + SLOT_SIZE) * SCALE;
+    let padding = (SLOT_SIZE - ICON_SIZE) / 2.0 * SCALE;
+    for slot in 0..HOTBAR_SIZE {
+        let x = left + size * slot as f32;
+        rect(colored, x, top, x + size, top + size, SLOT_COLOR);
+        if slot == player.inventory.selected {
+            frame(colored, x - SCALE, top - SCALE, x + size + SCALE, top + size + SCALE, SCALE, SELECTED_COLOR);
+        }
+        if let Some(stack) = &player.inventory.slots[slot] {
+            item(colored, icons, stack, world, window, x + padding, top + padding);
+        }
+    }
+}
+
+// where the menu's top left goes to center it on the screen
+pub fn menu_origin(menu: &Menu, width: f32, height: f32) -> (f32, f32) {
+    return ((width - MENU_WIDTH * SCALE) / 2.0, (height - menu.height * SCALE) / 2.0);
+}
+
+// the menu slot under a point on the screen
+pub fn menu_slot_at(menu: &Menu, x: f32, y: f32, width: f32, height: f32) -> Option<usize> {
+    let (left, top) = menu_origin(menu, width, height);
+    return slot_at(menu, (x - left) / SCALE, (y - top) / SCALE);
+}
+
+fn menu<T>(colored: &mut Vec<f32>, icons: &mut Vec<f32>, player: &Player, world: &World, window: &Window<T>, width: f32, height: f32) {
+    let Some(menu) = &player.menu else {
+        return;
+    };
+    rect(colored, 0.0, 0.0, width, height, SHADE_COLOR);
+    let (left, top) = menu_origin(menu, width, height);
+    rect(colored, left, top, left + MENU_WIDTH * SCALE, top + menu.height * SCALE, PANEL_COLOR);
+    text(colored, &menu.title, left + TITLE_POS.0 * SCALE, top + TITLE_POS.1 * SCALE, TITLE_COLOR);
+    let cursor = window.get_cursor_pos();
+    let hovered = menu_slot_at(menu, cursor.x, cursor.y, width, height);
+    let size = SLOT_SPACING * SCALE;
+    let padding = (SLOT_SPACING - ICON_SIZE) / 2.0 * SCALE;
+    for (i, slot) in menu.slots.iter().enumerate() {
+        let (x, y) = (left + slot.x * SCALE, top + slot.y * SCALE);
+        let color = if hovered == Some(i) { HOVER_COLOR } else { MENU_SLOT_COLOR };
+        rect(colored, x + SCALE, y + SCALE, x + size - SCALE, y + size - SCALE, color);
+        if let Some(stack) = menu.shown(i, player, world) {
+            item(colored, icons, &stack, world, window, x + padding, y + padding);
+        }
+    }
+    // the bars show the progress of the first block in the menu
+    let progress = menu.containers.iter().find_map(|x| match x {
+        ContainerRef::Block(pos) => world.block_entity(*pos),
+        _ => None,
+    }).map_or(Vec::new(), |x| x.progress());
+    for (bar, fill) in menu.bars.iter().zip(progress) {
+        let (x, y) = (left + bar.x * SCALE, top + bar.y * SCALE);
+        rect(colored, x, y, x + bar.width * SCALE, y + bar.height * SCALE, BAR_COLOR);
+        rect(colored, x, y, x + bar.width * SCALE * fill.clamp(0.0, 1.0), y + bar.height * SCALE, BAR_FILL_COLOR);
+    }
+    if let Some(carried) = &menu.carried {
+        let half = ICON_SIZE * SCALE / 2.0;
+        item(colored, icons, carried, world, window, cursor.x - half, cursor.y - half);
+    }
+}
+
+// an item's icon at x and y, and as the font has no digits yet a bar under it shows how full the stack is
+fn item<T>(colored: &mut Vec<f32>, icons: &mut Vec<f32>, stack: &ItemStack, world: &World, window: &Window<T>, x: f32, y: f32) {
+    let size = ICON_SIZE * SCALE;
+    icon(icons, stack, world, window, x, y, size);
+    let fill = stack.count as f32 / stack.max_stack as f32;
+    rect(colored, x, y + size, x + size * fill, y + size + SCALE, COUNT_COLOR);
+}
+
+fn icon<T>(vec: &mut Vec<f32>, stack: &ItemStack, world: &World, window: &Window<T>, x: f32, y: f32, size: f32) {
+    let item = &world.items[stack.item as usize];
+    let texture = match (&item.texture, item.block) {
+        (Some(texture), _) => texture.get_texture_name(),
+        (None, Some(block)) => match &world.blocks[block as usize].model {
+            ModelType::Block(model) => match model.texture {
+                BlockTextureType::None => return,
+                _ => model.get_texture(&Side::Front),
+            },
+            ModelType::Plant(plant) => plant.texture.get_texture_name(),
+        },
+        (None, None) => return,
+    };
+    // lit by full block light so it never gets darker at night
+    let shade = Shade {
+        ao: 1.0,
+        sky: 1.0,
+        block: (1.0, 1.0, 1.0),
+    };
+    let mut triangles = Vec::new();
+    let quad = Quad {
+        corners: [Vec3::new(x, y, 0.0), Vec3::new(x + size, y, 0.0), Vec3::new(x + size, y + size, 0.0), Vec3::new(x, y + size, 0.0)],
+        uvs: [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+        shades: [shade; 4],
+    };
+    Triangle::square_mapped(&mut triangles, quad, &window.shaders.get_texture(texture.as_str()));
+    for triangle in triangles.iter() {
+        triangle.to_points(vec);
+    }
+}
+
+// letters in capitals from x and y, anything that isn't a letter is left as a gap
+fn text(vec: &mut Vec<f32>, text: &str, x: f32, y: f32, color: (f32, f32, f32, f32)) {
+    for (i, c) in text.to_ascii_uppercase().chars().enumerate() {
+        if !c.is_ascii_uppercase() {
+            continue;
+        }
+        let glyph = FONT[(c as u8 - b'A') as usize];
+        let left = x + i as f32 * LETTER_WIDTH * SCALE;
+        for row in 0..5 {
+            for column in 0..3 {
+                if glyph >> (14 - row * 3 - column) & 1 == 1 {
+                    let (px, py) = (left + column as f32 * SCALE, y + row as f32 * SCALE);
+                    rect(vec, px, py, px + SCALE, py + SCALE, color);
+                }
+            }
+        }
+    }
+}
+
+// the outline of a rectangle, thickness pixels wide on the inside
+fn frame(vec: &mut Vec<f32>, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: (f32, f32, f32, f32)) {
+    rect(vec, x1, y1, x2, y1 + thickness, color);
+    rect(vec, x1, y2 - thickness, x2, y2, color);
+    rect(vec, x1, y1 + thickness, x1 + thickness, y2 - thickness, color);
+    rect(vec, x2 - thickness, y1 + thickness, x2, y2 - thickness, color);
+}
+
+fn rect(vec: &mut Vec<f32>, x1: f32, y1: f32, x2: f32, y2: f32, color: (f32, f32, f32, f32)) {
+    colored_square(vec, Vec3::new(x1, y1, 0.0), Vec3::new(x2, y1, 0.0), Vec3::new(x2, y2, 0.0), Vec3::new(x1, y2, 0.0), color);
+}
@@ -0,0 +1,196 @@
+use crate::items::ItemStack;
+use crate::nbt::Tag;
+use crate::world::World;
+
+pub const HOTBAR_SIZE: usize = 9;
+pub const PLAYER_INVENTORY_SIZE: usize = 36; // the hotbar is the first 9 slots
+
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+    pub selected: usize, // hotbar slot in hand
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        return Self {
+            slots: vec![None; size],
+            selected: 0,
+        }
+    }
+    pub fn select(&mut self, slot: usize) {
+        if slot < HOTBAR_SIZE.min(self.slots.len()) {
+            self.selected = slot;
+        }
+    }
+    // moves the selection by offset, wrapping around the hotbar
+    pub fn scroll(&mut self, offset: i32) {
+        let size = HOTBAR_SIZE.min(self.slots.len()) as i32;
+        if size > 0 {
+            self.selected = (self.selected as i32 + offset).rem_euclid(size) as usize;
+        }
+    }
+    pub fn selected_stack(&self) -> Option<&ItemStack> {
+        return self.slots.get(self.selected)?.as_ref();
+    }
+    // adds to stacks of the same item first and then to empty slots, returns what didn't fit
+    pub fn add(&mut self, stack: ItemStack) -> Option<ItemStack> {
+        let mut stack = stack;
+        for slot in self.slots.iter_mut().flatten() {
+            slot.merge(&mut stack);
+            if stack.is_empty() {
+                return None;
+            }
+        }
+        for slot in self.slots.iter_mut() {
+            if slot.is_none() {
+                // big stacks are spread over as many slots as they need
+                *slot = Some(stack.split(stack.max_stack));
+                if stack.is_empty() {
+                    return None;
+                }
+            }
+        }
+        return Some(stack);
+    }
+    // takes up to count items out of a slot
+    pub fn remove(&mut self, slot: usize, count: u32) -> Option<ItemStack> {
+        let stack = self.slots.get_mut(slot)?.as_mut()?;
+        let taken = stack.split(count);
+        if stack.is_empty() {
+            self.slots[slot] = None;
+        }
+        return Some(taken);
+    }
+    // takes the bigger half of a slot, like right clicking it
+    pub fn split(&mut self, slot: usize) -> Option<ItemStack> {
+        let count = self.slots.get(slot)?.as_ref()?.count;
+        return self.remove(slot, count.div_ceil(2));
+    }
+    // puts a stack into a slot, merging with what is there or swapping it out when it's
+    // a different item, returns what is left in hand
+    pub fn place(&mut self, slot: usize, stack: ItemStack) -> Option<ItemStack> {
+        let mut stack = stack;
+        match self.slots.get_mut(slot) {
+            None => Some(stack),
+            Some(Some(existing)) if existing.can_merge(&stack) => {
+                existing.merge(&mut stack);
+                if stack.is_empty() { None } else { Some(stack) }
+            }
+            Some(existing) => existing.replace(stack),
+        }
+    }
+    // moves a whole slot into another inventory as far as it fits, like shift clicking it
+    pub fn transfer(&mut self, slot: usize, other: &mut Inventory) {
+        let Some(stack) = self.slots.get_mut(slot).and_then(|x| x.take()) else {
+            return;
+        };
+        self.slots[slot] = other.add(stack);
+    }
+    // the filled slots as a list of stacks that know which slot they are in
+    pub fn save(&self, world: &World) -> Tag {
+        let mut list = Vec::new();
+        for (slot, stack) in self.slots.iter().enumerate() {
+            if let Some(stack) = stack {
+                let mut nbt = stack.save(world);
+                nbt.set("slot", Tag::Int(slot as i32));
+                list.push(Tag::Compound(nbt));
+            }
+        }
+        return Tag::List(list);
+    }
+    pub fn load(&mut self, tag: &Tag, world: &World) {
+        self.slots.iter_mut().for_each(|x| *x = None);
+        let Tag::List(list) = tag else {
+            return;
+        };
+        for nbt in list.iter() {
+            let Tag::Compound(nbt) = nbt else {
+                continue;
+            };
+            let slot = nbt.get_int("slot").unwrap_or(-1);
+            if slot >= 0 && (slot as usize) < self.slots.len() {
+                self.slots[slot as usize] = ItemStack::load(nbt, world);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stone(count: u32) -> ItemStack {
+        return ItemStack::new(1, count, 64);
+    }
+
+    #[test]
+    fn add_merges_before_filling_empty_slots() {
+        let mut inventory = Inventory::new(3);
+        inventory.slots[1] = Some(stone(60));
+        assert_eq!(inventory.add(stone(10)), None);
+        assert_eq!(inventory.slots[1].as_ref().unwrap().count, 64);
+        assert_eq!(inventory.slots[0].as_ref().unwrap().count, 6);
+        assert_eq!(inventory.add(stone(200)).unwrap().count, 78);
+    }
+
+    #[test]
+    fn different_nbt_does_not_merge() {
+        let mut named = stone(1);
+        named.nbt.set("name", crate::nbt::Tag::String("rock".to_string()));
+        let mut inventory = Inventory::new(2);
+        inventory.add(stone(1));
+        inventory.add(named);
+        assert!(inventory.slots.iter().all(|x| x.as_ref().unwrap().count == 1));
+    }
+
+    #[test]
+    fn split_and_place() {
+        let mut inventory = Inventory::new(2);
+        inventory.slots[0] = Some(stone(7));
+        let half = inventory.split(0).unwrap();
+        assert_eq!((half.count, inventory.slots[0].as_ref().unwrap().count), (4, 3));
+        assert_eq!(inventory.place(1, half), None);
+        let swapped = inventory.place(1, ItemStack::new(2, 1, 16)).unwrap();
+        assert_eq!((swapped.item, swapped.count), (1, 4));
+        assert_eq!(inventory.remove(0, 3).unwrap().count, 3);
+        assert!(inventory.slots[0].is_none());
+    }
+
+    #[test]
+    fn transfer_keeps_what_does_not_fit() {
+        let mut from = Inventory::new(1);
+        let mut to = Inventory::new(1);
+        from.slots[0] = Some(stone(50));
+        to.slots[0] = Some(stone(40));
+        from.transfer(0, &mut to);
+        assert_eq!(to.slots[0].as_ref().unwrap().count, 64);
+        assert_eq!(from.slots[0].as_ref().unwrap().count, 26);
+    }
+
+    #[test]
+    fn saves_items_by_id() {
+        let world = crate::testing::world(&["stone"], &[]);
+        let mut inventory = Inventory::new(3);
+        inventory.slots[0] = Some(ItemStack::new(1, 5, 1)); // a max stack that was changed since
+        inventory.slots[2] = Some(stone(7));
+        let Tag::List(mut list) = inventory.save(&world) else {
+            panic!("inventories save as lists");
+        };
+        if let Tag::Compound(nbt) = &mut list[1] {
+            nbt.set("id", Tag::String("minecraft:removed".to_string()));
+        }
+        let mut loaded = Inventory::new(3);
+        loaded.load(&Tag::List(list), &world);
+        assert_eq!(loaded.slots[0], Some(ItemStack::new(1, 5, 64)));
+        assert_eq!(loaded.slots[2], None);
+    }
+
+    #[test]
+    fn scroll_wraps_around_the_hotbar() {
+        let mut inventory = Inventory::new(PLAYER_INVENTORY_SIZE);
+        inventory.scroll(-1);
+        assert_eq!(inventory.selected, HOTBAR_SIZE - 1);
+        inventory.scroll(2);
+        assert_eq!(inventory.selected, 1);
+    }
+}
@@ -0,0 +1,178 @@
+use crate::graphics::TextureName;
+use crate::nbt::{Compound, Tag};
+use crate::world::World;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ToolType {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Hoe,
+    Sword,
+    Shears,
+}
+
+// tiers are ordered from worst to best, gold is fast but can only harvest what wood can
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ToolTier {
+    Wood,
+    Gold,
+    Stone,
+    Iron,
+    Diamond,
+}
+
+impl ToolTier {
+    pub const ALL: [ToolTier; 5] = [ToolTier::Wood, ToolTier::Gold, ToolTier::Stone, ToolTier::Iron, ToolTier::Diamond];
+
+    // what goes before the tool in its item name, like wooden pickaxe
+    pub fn material(&self) -> &'static str {
+        match self {
+            Self::Wood => "wooden",
+            Self::Gold => "golden",
+            Self::Stone => "stone",
+            Self::Iron => "iron",
+            Self::Diamond => "diamond",
+        }
+    }
+    // how many times faster than a hand it breaks the blocks it is made for
+    pub fn speed(&self) -> f32 {
+        match self {
+            Self::Wood => 2.0,
+            Self::Stone => 4.0,
+            Self::Iron => 6.0,
+            Self::Diamond => 8.0,
+            Self::Gold => 12.0,
+        }
+    }
+    // which blocks it can harvest, a block needing a tier needs a tool of at least its level
+    pub fn level(&self) -> u8 {
+        match self {
+            Self::Wood | Self::Gold => 0,
+            Self::Stone => 1,
+            Self::Iron => 2,
+            Self::Diamond => 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Tool {
+    pub kind: ToolType,
+    pub tier: ToolTier,
+}
+
+#[derive(Clone)]
+pub struct ItemData {
+    pub name: String,
+    pub max_stack: u32,
+    pub block: Option<u64>, // the block it places
+    pub tool: Option<Tool>,
+    pub burn_time: u32, // ticks it keeps a furnace lit, 0 if it isn't fuel
+    pub texture: Option<Box<dyn TextureName>>, // shown in the inventory, block items show their block instead
+}
+
+impl ItemData {
+    // an item that isn't a block, tools and fuel are set on it afterwards
+    pub fn new(name: &str, max_stack: u32) -> Self {
+        return Self {
+            name: name.to_string(),
+            max_stack,
+            block: None,
+            tool: None,
+            burn_time: 0,
+            texture: None,
+        }
+    }
+    // the item every block gets when it is registered
+    pub fn block_item(name: &str, block: u64) -> Self {
+        let mut item = Self::new(name, 64);
+        item.block = Some(block);
+        return item;
+    }
+    // how data files refer to it, like minecraft:dirt_block for "dirt block"
+    pub fn id(&self) -> String {
+        return format!("minecraft:{}", self.name.replace(' ', "_"));
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ItemStack {
+    pub item: u64,
+    pub count: u32,
+    pub max_stack: u32,
+    pub nbt: Compound,
+}
+
+impl ItemStack {
+    pub fn new(item: u64, count: u32, max_stack: u32) -> Self {
+        return Self {
+            item,
+            count,
+            max_stack,
+            nbt: Compound::new(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.count == 0;
+    }
+    pub fn is_full(&self) -> bool {
+        return self.count >= self.max_stack;
+    }
+    // stacks only merge when they are the same item with the same nbt
+    pub fn can_merge(&self, other: &ItemStack) -> bool {
+        return self.item == other.item && self.nbt == other.nbt;
+    }
+    // moves as much of other into this stack as fits, other is left with the rest
+    pub fn merge(&mut self, other: &mut ItemStack) {
+        if !self.can_merge(other) {
+            return;
+        }
+        let moved = other.count.min(self.max_stack.saturating_sub(self.count));
+        self.count += moved;
+        other.count -= moved;
+    }
+    // takes up to count items off into a new stack
+    pub fn split(&mut self, count: u32) -> ItemStack {
+        let taken = count.min(self.count);
+        self.count -= taken;
+        let mut stack = self.clone();
+        stack.count = taken;
+        return stack;
+    }
+    // the level of an enchantment like minecraft:silk_touch in its nbt, 0 without it
+    pub fn enchantment_level(&self, id: &str) -> u32 {
+        let Some(Tag::List(enchantments)) = self.nbt.get("enchantments") else {
+            return 0;
+        };
+        for enchantment in enchantments.iter() {
+            if let Tag::Compound(enchantment) = enchantment {
+                if enchantment.get_string("id") == Some(id) {
+                    return enchantment.get_int("lvl").unwrap_or(1).max(0) as u32;
+                }
+            }
+        }
+        return 0;
+    }
+    // stored by the item's id so it survives items being registered in another order
+    pub fn save(&self, world: &World) -> Compound {
+        let mut nbt = Compound::new();
+        nbt.set("id", Tag::String(world.items[self.item as usize].id()));
+        nbt.set("count", Tag::Int(self.count as i32));
+        if !self.nbt.is_empty() {
+            nbt.set("tag", Tag::Compound(self.nbt.clone()));
+        }
+        return nbt;
+    }
+    // None for items that aren't registered anymore
+    pub fn load(nbt: &Compound, world: &World) -> Option<Self> {
+        let item = world.find_item(nbt.get_string("id")?)?;
+        let count = nbt.get_int("count")?;
+        if count <= 0 {
+            return None;
+        }
+        let mut stack = world.item_stack(item, count as u32);
+        stack.nbt = nbt.get_compound("tag").cloned().unwrap_or_default();
+        return Some(stack);
+    }
+}
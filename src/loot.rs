@@ -0,0 +1,538 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::crafting::{namespaced, tag_items};
+use crate::graphics::files;
+use crate::inventory::Inventory;
+use crate::items::{ItemData, ItemStack, ToolType};
+use crate::json::{self, Json};
+use crate::world::World;
+
+pub const LOOT_TABLE_DIR: &str = "./data/loot_tables";
+// a table is named after its folder and file, like minecraft:blocks/dirt_block
+const LOOT_TABLE_KINDS: [&str; 2] = ["blocks", "chests"];
+
+// a number picked again every time it is used
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum NumberProvider {
+    Constant(f32),
+    Uniform(f32, f32), // min and max
+    Binomial(u32, f32), // n tries that each add 1 with chance p
+}
+
+impl NumberProvider {
+    pub fn float<R: Rng>(&self, rng: &mut R) -> f32 {
+        match *self {
+            Self::Constant(value) => value,
+            Self::Uniform(min, max) => if max > min { rng.gen_range(min..max) } else { min },
+            Self::Binomial(n, p) => (0..n).filter(|_| rng.gen::<f32>() < p).count() as f32,
+        }
+    }
+    // a whole number, uniform includes both ends
+    pub fn int<R: Rng>(&self, rng: &mut R) -> i32 {
+        match *self {
+            Self::Uniform(min, max) => {
+                let (min, max) = (min.round() as i32, max.round() as i32);
+                if max > min { rng.gen_range(min..=max) } else { min }
+            }
+            _ => self.float(rng).round() as i32,
+        }
+    }
+}
+
+// what the tool that broke the block has to be, every part that is set has to match
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct ToolPredicate {
+    pub items: Option<Vec<u64>>,
+    pub kind: Option<ToolType>,
+    pub enchantments: Vec<(String, u32)>, // at least this level of each
+}
+
+impl ToolPredicate {
+    pub fn matches(&self, tool: Option<&ItemStack>, items: &[ItemData]) -> bool {
+        let Some(tool) = tool else {
+            return false;
+        };
+        if self.items.as_ref().is_some_and(|x| !x.contains(&tool.item)) {
+            return false;
+        }
+        if self.kind.is_some() && self.kind != items[tool.item as usize].tool.map(|x| x.kind) {
+            return false;
+        }
+        return self.enchantments.iter().all(|(id, level)| tool.enchantment_level(id) >= *level);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LootCondition {
+    MatchTool(ToolPredicate),
+    RandomChance(f32),
+    Inverted(Box<LootCondition>),
+    AnyOf(Vec<LootCondition>),
+    AllOf(Vec<LootCondition>),
+}
+
+impl LootCondition {
+    pub fn test<R: Rng>(&self, context: &LootContext, items: &[ItemData], rng: &mut R) -> bool {
+        match self {
+            Self::MatchTool(predicate) => predicate.matches(context.tool, items),
+            Self::RandomChance(chance) => rng.gen::<f32>() < *chance,
+            Self::Inverted(condition) => !condition.test(context, items, rng),
+            Self::AnyOf(conditions) => conditions.iter().any(|x| x.test(context, items, rng)),
+            Self::AllOf(conditions) => all(conditions, context, items, rng),
+        }
+    }
+}
+
+fn all<R: Rng>(conditions: &[LootCondition], context: &LootContext, items: &[ItemData], rng: &mut R) -> bool {
+    return conditions.iter().all(|x| x.test(context, items, rng));
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LootFunctionKind {
+    SetCount {
+        count: NumberProvider,
+        add: bool, // adds to the count instead of replacing it
+    },
+    LimitCount {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+}
+
+// changes a stack after it is picked, only when its conditions pass
+#[derive(Clone, PartialEq, Debug)]
+pub struct LootFunction {
+    pub kind: LootFunctionKind,
+    pub conditions: Vec<LootCondition>,
+}
+
+impl LootFunction {
+    pub fn apply<R: Rng>(&self, stack: &mut ItemStack, context: &LootContext, items: &[ItemData], rng: &mut R) {
+        if !all(&self.conditions, context, items, rng) {
+            return;
+        }
+        let count = stack.count as i32;
+        let count = match self.kind {
+            LootFunctionKind::SetCount { count: provider, add } => provider.int(rng) + if add { count } else { 0 },
+            LootFunctionKind::LimitCount { min, max } => count.max(min.unwrap_or(i32::MIN)).min(max.unwrap_or(i32::MAX)),
+        };
+        stack.count = count.max(0) as u32;
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum LootEntryKind {
+    Empty,
+    Item(u64),
+    // all the tag's items at once, or expanded into one choice per item
+    Tag {
+        items: Vec<u64>,
+        expand: bool,
+    },
+    // the first child whose conditions pass
+    Alternatives(Vec<LootEntry>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct LootEntry {
+    pub kind: LootEntryKind,
+    pub weight: u32,
+    pub conditions: Vec<LootCondition>,
+    pub functions: Vec<LootFunction>,
+}
+
+impl LootEntry {
+    // adds what this entry can give to the choices of a roll, false when its conditions fail
+    fn choices<'a, R: Rng>(&'a self, choices: &mut Vec<(&'a LootEntry, Vec<u64>)>, context: &LootContext, items: &[ItemData], rng: &mut R) -> bool {
+        if !all(&self.conditions, context, items, rng) {
+            return false;
+        }
+        match &self.kind {
+            LootEntryKind::Empty => choices.push((self, Vec::new())),
+            LootEntryKind::Item(item) => choices.push((self, vec![*item])),
+            LootEntryKind::Tag { items, expand: false } => choices.push((self, items.clone())),
+            LootEntryKind::Tag { items, expand: true } => choices.extend(items.iter().map(|x| (self, vec![*x]))),
+            LootEntryKind::Alternatives(children) => {
+                return children.iter().any(|x| x.choices(choices, context, items, rng));
+            }
+        }
+        return true;
+    }
+}
+
+// rolled a number of times, each roll picks one entry by weight
+#[derive(Clone, PartialEq, Debug)]
+pub struct LootPool {
+    pub rolls: NumberProvider,
+    pub entries: Vec<LootEntry>,
+    pub conditions: Vec<LootCondition>,
+    pub functions: Vec<LootFunction>,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct LootTable {
+    pub pools: Vec<LootPool>,
+    pub functions: Vec<LootFunction>,
+}
+
+// what the conditions can check, like the tool a block was broken with
+#[derive(Clone, Copy, Default)]
+pub struct LootContext<'a> {
+    pub tool: Option<&'a ItemStack>,
+}
+
+impl LootTable {
+    // stacks bigger than an item's max stack are split up, empty ones are left out
+    pub fn roll<R: Rng>(&self, context: &LootContext, items: &[ItemData], rng: &mut R) -> Vec<ItemStack> {
+        let mut stacks = Vec::new();
+        for pool in self.pools.iter() {
+            if !all(&pool.conditions, context, items, rng) {
+                continue;
+            }
+            for _ in 0..pool.rolls.int(rng).max(0) {
+                let mut choices = Vec::new();
+                for entry in pool.entries.iter() {
+                    entry.choices(&mut choices, context, items, rng);
+                }
+                let total: u32 = choices.iter().map(|(entry, _)| entry.weight).sum();
+                if total == 0 {
+                    continue;
+                }
+                let mut pick = rng.gen_range(0..total);
+                let Some((entry, picked)) = choices.into_iter().find(|(entry, _)| {
+                    if pick < entry.weight {
+                        return true;
+                    }
+                    pick -= entry.weight;
+                    return false;
+                }) else {
+                    continue;
+                };
+                for item in picked {
+                    let mut stack = ItemStack::new(item, 1, items[item as usize].max_stack);
+                    for function in entry.functions.iter().chain(pool.functions.iter()).chain(self.functions.iter()) {
+                        function.apply(&mut stack, context, items, rng);
+                    }
+                    while stack.count > 0 {
+                        let size = stack.max_stack.max(1);
+                        stacks.push(stack.split(size));
+                    }
+                }
+            }
+        }
+        return stacks;
+    }
+}
+
+// puts the stacks into random empty slots, splitting some of them up while there are slots
+// to spare so a chest's loot is spread out, returns what didn't fit
+pub fn fill_inventory<R: Rng>(inventory: &mut Inventory, stacks: Vec<ItemStack>, rng: &mut R) -> Vec<ItemStack> {
+    let mut empty: Vec<usize> = (0..inventory.slots.len()).filter(|x| inventory.slots[*x].is_none()).collect();
+    empty.shuffle(rng);
+    let mut stacks = stacks;
+    while stacks.len() < empty.len() {
+        let splittable: Vec<usize> = (0..stacks.len()).filter(|x| stacks[*x].count > 1).collect();
+        let Some(&index) = splittable.choose(rng) else {
+            break;
+        };
+        let half = rng.gen_range(1..=stacks[index].count / 2);
+        let part = stacks[index].split(half);
+        stacks.push(part);
+    }
+    stacks.shuffle(rng);
+    let mut leftover = Vec::new();
+    for stack in stacks {
+        match empty.pop() {
+            Some(slot) => inventory.slots[slot] = Some(stack),
+            None => leftover.extend(inventory.add(stack)),
+        }
+    }
+    return leftover;
+}
+
+// loot tables use the same json as minecraft, the tags they use have to be loaded first
+pub fn load_loot_tables(world: &mut World, dev: &bool) {
+    for kind in LOOT_TABLE_KINDS {
+        for path in files::list_files(&format!("{}/{}", LOOT_TABLE_DIR, kind), dev).unwrap_or_default() {
+            let Some(name) = path.rsplit('/').next().and_then(|x| x.strip_suffix(".json")) else {
+                continue;
+            };
+            let name = format!("minecraft:{}/{}", kind, name);
+            match files::load_file(&path, dev).map_err(|e| e.to_string()).and_then(|x| json::parse(&x)).and_then(|x| parse_loot_table(world, &x)) {
+                Ok(table) => {
+                    world.loot_tables.insert(name, table);
+                }
+                Err(e) => eprintln!("failed to load {}: {}", path, e),
+            }
+        }
+    }
+}
+
+pub fn parse_loot_table(world: &World, json: &Json) -> Result<LootTable, String> {
+    let mut pools = Vec::new();
+    for pool in list(json, "pools") {
+        let mut entries = Vec::new();
+        for entry in list(pool, "entries") {
+            entries.push(parse_entry(world, entry)?);
+        }
+        pools.push(LootPool {
+            rolls: pool.get("rolls").map_or(Ok(NumberProvider::Constant(1.0)), parse_number)?,
+            entries,
+            conditions: parse_conditions(world, pool)?,
+            functions: parse_functions(world, pool)?,
+        });
+    }
+    return Ok(LootTable {
+        pools,
+        functions: parse_functions(world, json)?,
+    });
+}
+
+// a missing list is the same as an empty one
+fn list<'a>(json: &'a Json, key: &str) -> &'a [Json] {
+    return json.get(key).and_then(|x| x.as_array()).map_or(&[], |x| x.as_slice());
+}
+
+fn kind(json: &Json) -> Result<String, String> {
+    return json.get("type").or(json.get("condition")).or(json.get("function")).and_then(|x| x.as_str()).map(namespaced).ok_or("missing type".to_string());
+}
+
+fn parse_entry(world: &World, json: &Json) -> Result<LootEntry, String> {
+    let name = || json.get("name").and_then(|x| x.as_str()).ok_or("missing name");
+    let kind = match kind(json)?.as_str() {
+        "minecraft:empty" => LootEntryKind::Empty,
+        "minecraft:item" => LootEntryKind::Item(world.find_item(name()?).ok_or(format!("unknown item {}", name()?))?),
+        "minecraft:tag" => LootEntryKind::Tag {
+            items: tag_items(world, name()?)?,
+            expand: json.get("expand").and_then(|x| x.as_bool()).unwrap_or(false),
+        },
+        "minecraft:alternatives" => LootEntryKind::Alternatives(list(json, "children").iter().map(|x| parse_entry(world, x)).collect::<Result<_, _>>()?),
+        other => return Err(format!("unknown loot entry {}", other)),
+    };
+    return Ok(LootEntry {
+        kind,
+        weight: json.get("weight").and_then(|x| x.as_f64()).unwrap_or(1.0).max(0.0) as u32,
+        conditions: parse_conditions(world, json)?,
+        functions: parse_functions(world, json)?,
+    });
+}
+
+fn parse_conditions(world: &World, json: &Json) -> Result<Vec<LootCondition>, String> {
+    let mut conditions = Vec::new();
+    for condition in list(json, "conditions") {
+        conditions.extend(parse_condition(world, condition)?);
+    }
+    return Ok(conditions);
+}
+
+// None for conditions that always pass here
+fn parse_condition(world: &World, json: &Json) -> Result<Option<LootCondition>, String> {
+    let terms = || -> Result<Vec<LootCondition>, String> {
+        let mut terms = Vec::new();
+        for term in list(json, "terms") {
+            terms.extend(parse_condition(world, term)?);
+        }
+        return Ok(terms);
+    };
+    let condition = match kind(json)?.as_str() {
+        "minecraft:match_tool" => LootCondition::MatchTool(parse_tool_predicate(world, json.get("predicate").unwrap_or(&Json::Null))?),
+        "minecraft:random_chance" => LootCondition::RandomChance(json.get("chance").and_then(|x| x.as_f64()).ok_or("missing chance")? as f32),
+        // an inverted condition that always passes never does
+        "minecraft:inverted" => match parse_condition(world, json.get("term").ok_or("missing term")?)? {
+            Some(term) => LootCondition::Inverted(Box::new(term)),
+            None => LootCondition::AnyOf(Vec::new()),
+        },
+        "minecraft:any_of" | "minecraft:alternative" => LootCondition::AnyOf(terms()?),
+        "minecraft:all_of" => LootCondition::AllOf(terms()?),
+        // nothing explodes yet
+        "minecraft:survives_explosion" => return Ok(None),
+        other => return Err(format!("unknown loot condition {}", other)),
+    };
+    return Ok(Some(condition));
+}
+
+fn parse_tool_predicate(world: &World, json: &Json) -> Result<ToolPredicate, String> {
+    let mut predicate = ToolPredicate::default();
+    // the items are a list of ids or a #tag, older versions have a separate tag
+    match json.get("items") {
+        Some(Json::String(tag)) if tag.starts_with('#') => predicate.items = Some(tag_items(world, &tag[1..])?),
+        Some(Json::String(id)) => predicate.items = Some(vec![world.find_item(id).ok_or(format!("unknown item {}", id))?]),
+        Some(Json::Array(ids)) => {
+            let ids: Vec<&str> = ids.iter().map(|x| x.as_str().ok_or("items must be ids")).collect::<Result<_, _>>()?;
+            predicate.items = Some(ids.iter().map(|id| world.find_item(id).ok_or(format!("unknown item {}", id))).collect::<Result<_, _>>()?);
+        }
+        _ => (),
+    }
+    if let Some(tag) = json.get("tag").and_then(|x| x.as_str()) {
+        predicate.items = Some(tag_items(world, tag)?);
+    }
+    if let Some(tool) = json.get("tool").and_then(|x| x.as_str()) {
+        predicate.kind = Some(tool_type(tool).ok_or(format!("unknown tool {}", tool))?);
+    }
+    for enchantment in list(json, "enchantments") {
+        let id = enchantment.get("enchantment").and_then(|x| x.as_str()).ok_or("missing enchantment")?;
+        // levels is either exact or a range, only the lowest level matters
+        let level = match enchantment.get("levels") {
+            Some(Json::Number(level)) => *level,
+            Some(levels) => levels.get("min").and_then(|x| x.as_f64()).unwrap_or(1.0),
+            None => 1.0,
+        };
+        predicate.enchantments.push((namespaced(id), level.max(1.0) as u32));
+    }
+    return Ok(predicate);
+}
+
+fn tool_type(name: &str) -> Option<ToolType> {
+    match name {
+        "pickaxe" => Some(ToolType::Pickaxe),
+        "axe" => Some(ToolType::Axe),
+        "shovel" => Some(ToolType::Shovel),
+        "hoe" => Some(ToolType::Hoe),
+        "sword" => Some(ToolType::Sword),
+        "shears" => Some(ToolType::Shears),
+        _ => None,
+    }
+}
+
+fn parse_functions(world: &World, json: &Json) -> Result<Vec<LootFunction>, String> {
+    let mut functions = Vec::new();
+    for function in list(json, "functions") {
+        let kind = match kind(function)?.as_str() {
+            "minecraft:set_count" => LootFunctionKind::SetCount {
+                count: parse_number(function.get("count").ok_or("missing count")?)?,
+                add: function.get("add").and_then(|x| x.as_bool()).unwrap_or(false),
+            },
+            "minecraft:limit_count" => {
+                let limit = function.get("limit").ok_or("missing limit")?;
+                match limit {
+                    Json::Number(limit) => LootFunctionKind::LimitCount { min: Some(*limit as i32), max: Some(*limit as i32) },
+                    _ => LootFunctionKind::LimitCount {
+                        min: limit.get("min").and_then(|x| x.as_f64()).map(|x| x as i32),
+                        max: limit.get("max").and_then(|x| x.as_f64()).map(|x| x as i32),
+                    },
+                }
+            }
+            // nothing explodes yet
+            "minecraft:explosion_decay" => continue,
+            other => return Err(format!("unknown loot function {}", other)),
+        };
+        functions.push(LootFunction {
+            kind,
+            conditions: parse_conditions(world, function)?,
+        });
+    }
+    return Ok(functions);
+}
+
+// a plain number or an object with a type, uniform when it only has a min and max
+fn parse_number(json: &Json) -> Result<NumberProvider, String> {
+    if let Some(value) = json.as_f64() {
+        return Ok(NumberProvider::Constant(value as f32));
+    }
+    let number = |key: &str| json.get(key).and_then(|x| x.as_f64()).map(|x| x as f32).ok_or(format!("missing {}", key));
+    let kind = json.get("type").and_then(|x| x.as_str()).map(namespaced);
+    match kind.as_deref() {
+        Some("minecraft:constant") => Ok(NumberProvider::Constant(number("value")?)),
+        Some("minecraft:uniform") | None => Ok(NumberProvider::Uniform(number("min")?, number("max")?)),
+        Some("minecraft:binomial") => Ok(NumberProvider::Binomial(number("n")?.max(0.0) as u32, number("p")?)),
+        Some(other) => Err(format!("unknown number provider {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::items::Tool;
+    use crate::items::ToolTier;
+    use crate::nbt::{Compound, Tag};
+    use crate::testing;
+
+    // items 1 and 2 are the ore and gem, 3 is a pickaxe
+    fn world() -> World {
+        let mut world = testing::world(&[], &["ore", "gem", "pickaxe"]);
+        world.items[3].tool = Some(Tool { kind: ToolType::Pickaxe, tier: ToolTier::Iron });
+        world.items[3].max_stack = 1;
+        return world;
+    }
+
+    fn table(world: &World, text: &str) -> LootTable {
+        return parse_loot_table(world, &json::parse(text).unwrap()).unwrap();
+    }
+
+    const ORE: &str = r#"{
+        "pools": [{
+            "rolls": 1,
+            "entries": [{
+                "type": "minecraft:alternatives",
+                "children": [
+                    {
+                        "type": "minecraft:item",
+                        "name": "minecraft:ore",
+                        "conditions": [{
+                            "condition": "minecraft:match_tool",
+                            "predicate": { "enchantments": [{ "enchantment": "minecraft:silk_touch", "levels": { "min": 1 } }] }
+                        }]
+                    },
+                    {
+                        "type": "minecraft:item",
+                        "name": "minecraft:gem",
+                        "conditions": [{ "condition": "minecraft:match_tool", "predicate": { "tool": "pickaxe" } }],
+                        "functions": [{ "function": "minecraft:set_count", "count": { "min": 2, "max": 5 } }]
+                    }
+                ]
+            }]
+        }]
+    }"#;
+
+    #[test]
+    fn conditions_pick_the_drop() {
+        let world = world();
+        let table = table(&world, ORE);
+        let mut rng = StdRng::seed_from_u64(0);
+        let roll = |tool: Option<&ItemStack>, rng: &mut StdRng| table.roll(&LootContext { tool }, &world.items, rng);
+
+        assert!(roll(None, &mut rng).is_empty());
+        let pickaxe = world.item_stack(3, 1);
+        for _ in 0..20 {
+            let drops = roll(Some(&pickaxe), &mut rng);
+            assert_eq!(drops.len(), 1);
+            assert_eq!(drops[0].item, 2);
+            assert!((2..=5).contains(&drops[0].count));
+        }
+
+        let mut silk_touch = pickaxe.clone();
+        let mut enchantment = Compound::new();
+        enchantment.set("id", Tag::String("minecraft:silk_touch".to_string()));
+        enchantment.set("lvl", Tag::Int(1));
+        silk_touch.nbt.set("enchantments", Tag::List(vec![Tag::Compound(enchantment)]));
+        assert_eq!(roll(Some(&silk_touch), &mut rng).iter().map(|x| (x.item, x.count)).collect::<Vec<_>>(), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn same_seed_same_loot() {
+        let world = world();
+        let table = table(&world, r#"{
+            "pools": [{
+                "rolls": { "type": "minecraft:uniform", "min": 2, "max": 6 },
+                "entries": [
+                    { "type": "minecraft:item", "name": "ore", "weight": 3, "functions": [{ "function": "set_count", "count": 100 }] },
+                    { "type": "minecraft:item", "name": "gem", "conditions": [{ "condition": "random_chance", "chance": 0.5 }] },
+                    { "type": "minecraft:empty" }
+                ]
+            }]
+        }"#);
+        let loot = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut inventory = Inventory::new(27);
+            let stacks = table.roll(&LootContext::default(), &world.items, &mut rng);
+            // 100 ore is more than a stack
+            assert!(stacks.iter().all(|x| x.count <= x.max_stack));
+            assert!(fill_inventory(&mut inventory, stacks, &mut rng).is_empty());
+            return inventory.slots;
+        };
+        assert_eq!(loot(7), loot(7));
+        assert!((0..10).any(|seed| loot(seed) != loot(7)));
+    }
+}
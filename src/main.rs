@@ -2,28 +2,52 @@ mod graphics;
 use graphics::{*, draw::*};
 mod world;
 use world::*;
+mod sky;
+use sky::Sky;
+mod physics;
+mod player;
+use player::*;
+mod hud;
+mod raycast;
+use raycast::*;
+mod items;
+use items::*;
+mod inventory;
+mod nbt;
+mod json;
+mod crafting;
+mod furnace;
+mod container;
+mod chest;
+mod loot;
+mod mining;
+use mining::*;
+#[cfg(test)]
+mod testing;
 
-use nalgebra_glm as glm;
+use image::{DynamicImage, RgbaImage, Rgba};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 const DEV: bool = true;
+const SAVE_FILE: &str = "./saves/world.dat";
+// ticks between breaking or placing blocks while the mouse button is held
+const BREAK_DELAY: u32 = 5;
+const PLACE_DELAY: u32 = 4;
+const DESTROY_STAGES: u32 = 10;
+const OUTLINE_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.4);
+const PICKUP_REACH: (f32, f32, f32) = (1.0, 0.5, 1.0); // how far around the player items get picked up
+const STARTER_LOOT: &str = "minecraft:chests/starter";
 
 struct GameData {
    player: Player,
    keys: Keys,
    sensitivity: f32,
+   render_distance: u32, // in chunks
    world: World,
-}
-
-struct Player {
-    speed: f32,
-}
-
-impl Player {
-    pub fn new() -> Self {
-        return Self {
-            speed: 0.02, 
-        }
-    }
+   sky: Sky,
+   target: Option<RayHit>, // block the player is looking at
+   use_cooldown: u32, // ticks until the held mouse button acts again
+   mining: Mining,
 }
 
 struct Keys {
@@ -33,6 +57,9 @@ struct Keys {
     d: bool,
     space: bool,
     shift: bool,
+    control: bool,
+    left_mouse: bool,
+    right_mouse: bool,
 }
 
 impl Keys {
@@ -44,6 +71,9 @@ impl Keys {
             d: false,
             space: false,
             shift: false,
+            control: false,
+            left_mouse: false,
+            right_mouse: false,
         }
     }
 }
@@ -52,51 +82,304 @@ enum Textures {
     GrassBlockTop,
     GrassBlockSide,
     DirtBlock,
+    DestroyStage(u32),
+    FurnaceTop,
+    FurnaceSide,
+    FurnaceFront,
+    FurnaceFrontLit,
+    Coal,
+    ChestTop,
+    ChestSide,
+    ChestFront,
+    CraftingTableTop,
+    CraftingTableSide,
+    Water,
+    Lava,
+    OakLeaves,
+    Grass,
+    Tool(ToolType, ToolTier),
 }
 
 impl TextureName for Textures {
     fn get_texture_name(&self) -> String {
         match self {
-            Self::GrassBlockTop => "grass-block-top",
-            Self::GrassBlockSide => "grass-block-side",
-            Self::DirtBlock => "dirt-block",
-        }.to_string()
+            Self::GrassBlockTop => "grass-block-top".to_string(),
+            Self::GrassBlockSide => "grass-block-side".to_string(),
+            Self::DirtBlock => "dirt-block".to_string(),
+            Self::DestroyStage(stage) => format!("destroy-stage-{}", stage),
+            Self::FurnaceTop => "furnace-top".to_string(),
+            Self::FurnaceSide => "furnace-side".to_string(),
+            Self::FurnaceFront => "furnace-front".to_string(),
+            Self::FurnaceFrontLit => "furnace-front-lit".to_string(),
+            Self::Coal => "coal".to_string(),
+            Self::ChestTop => "chest-top".to_string(),
+            Self::ChestSide => "chest-side".to_string(),
+            Self::ChestFront => "chest-front".to_string(),
+            Self::CraftingTableTop => "crafting-table-top".to_string(),
+            Self::CraftingTableSide => "crafting-table-side".to_string(),
+            Self::Water => "water".to_string(),
+            Self::Lava => "lava".to_string(),
+            Self::OakLeaves => "oak-leaves".to_string(),
+            Self::Grass => "grass".to_string(),
+            Self::Tool(kind, tier) => tool_name(*kind, *tier).replace(' ', "-"),
+        }
     }
 }
 
+// cracks that grow out from the middle, each stage has the cracks of the one before it
+fn destroy_stage_textures() -> Vec<DynamicImage> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut order = [[usize::MAX; 16]; 16];
+    let mut count = 0;
+    let mut tips: Vec<(f32, f32, f32)> = (0..5).map(|i| (7.5, 7.5, i as f32 * 1.25 + rng.gen_range(0.0..0.5))).collect();
+    while let Some((x, y, angle)) = tips.pop() {
+        if x < 0.0 || y < 0.0 || x >= 16.0 || y >= 16.0 {
+            continue;
+        }
+        if order[x as usize][y as usize] == usize::MAX {
+            order[x as usize][y as usize] = count;
+            count += 1;
+        }
+        let angle = angle + rng.gen_range(-0.5..0.5);
+        tips.insert(0, (x + angle.cos(), y + angle.sin(), angle));
+        if rng.gen_bool(0.08) {
+            tips.insert(0, (x, y, angle + rng.gen_range(-1.2..1.2)));
+        }
+    }
+    return (0..DESTROY_STAGES).map(|stage| {
+        let shown = count * (stage as usize + 1) / DESTROY_STAGES as usize;
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+            if order[x as usize][y as usize] < shown {
+                Rgba([20, 20, 20, 150])
+            } else {
+                Rgba([0, 0, 0, 0])
+            }
+        }))
+    }).collect();
+}
+
+// rough gray stone, the front has an opening that glows while lit
+fn furnace_textures() -> [(Textures, DynamicImage); 4] {
+    let mut rng = StdRng::seed_from_u64(1);
+    let stone: Vec<u8> = (0..256).map(|_| rng.gen_range(90..140)).collect();
+    let texture = |opening: Option<bool>| DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        let gray = stone[(y * 16 + x) as usize];
+        // a darker rim around the edge
+        let gray = if x == 0 || y == 0 || x == 15 || y == 15 { gray - 40 } else { gray };
+        match opening {
+            Some(lit) if (3..13).contains(&x) && (8..14).contains(&y) => {
+                if lit {
+                    let heat = (y - 8) as u8 * 25;
+                    Rgba([255, 120 + heat, 20 + heat / 2, 255])
+                } else {
+                    Rgba([25, 25, 25, 255])
+                }
+            }
+            _ => Rgba([gray, gray, gray, 255]),
+        }
+    }));
+    return [
+        (Textures::FurnaceTop, texture(None)),
+        (Textures::FurnaceSide, texture(None)),
+        (Textures::FurnaceFront, texture(Some(false))),
+        (Textures::FurnaceFrontLit, texture(Some(true))),
+    ];
+}
+
+// planks with a dark band around the lid, the front has a latch
+fn chest_textures() -> [(Textures, DynamicImage); 3] {
+    let mut rng = StdRng::seed_from_u64(2);
+    let grain: Vec<i16> = (0..256).map(|_| rng.gen_range(-12..12)).collect();
+    let texture = |side: bool, latch: bool| DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        let rim = x == 0 || y == 0 || x == 15 || y == 15 || side && y == 5;
+        if latch && (7..9).contains(&x) && (4..8).contains(&y) {
+            return Rgba([190, 190, 200, 255]);
+        }
+        let shade = grain[(y * 16 + x) as usize] + if rim { -50 } else { 0 };
+        Rgba([(160 + shade) as u8, (110 + shade) as u8, (55 + shade) as u8, 255])
+    }));
+    return [
+        (Textures::ChestTop, texture(false, false)),
+        (Textures::ChestSide, texture(true, false)),
+        (Textures::ChestFront, texture(true, true)),
+    ];
+}
+
+// planks with a grid drawn on top, the sides have a dark tool hanging on them
+fn crafting_table_textures() -> [(Textures, DynamicImage); 2] {
+    let mut rng = StdRng::seed_from_u64(3);
+    let grain: Vec<i16> = (0..256).map(|_| rng.gen_range(-12..12)).collect();
+    let texture = |top: bool| DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        let rim = x == 0 || y == 0 || x == 15 || y == 15;
+        let lines = top && (x % 5 == 0 || y % 5 == 0);
+        let tool = !top && (4..12).contains(&x) && (3..7).contains(&y) || !top && (7..9).contains(&x) && (7..13).contains(&y);
+        if tool {
+            return Rgba([70, 70, 75, 255]);
+        }
+        let shade = grain[(y * 16 + x) as usize] + if rim || lines { -45 } else { 0 };
+        Rgba([(175 + shade) as u8, (130 + shade) as u8, (75 + shade) as u8, 255])
+    }));
+    return [
+        (Textures::CraftingTableTop, texture(true)),
+        (Textures::CraftingTableSide, texture(false)),
+    ];
+}
+
+// ripples, water is see through and lava glows
+fn liquid_textures() -> [(Textures, DynamicImage); 2] {
+    let mut rng = StdRng::seed_from_u64(4);
+    let ripples: Vec<i16> = (0..256).map(|_| rng.gen_range(-15..15)).collect();
+    let texture = |color: (i16, i16, i16), alpha: u8| DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        let shade = ripples[(y * 16 + x) as usize] + if (x + y * 2) % 7 == 0 { 25 } else { 0 };
+        let channel = |c: i16| (c + shade).clamp(0, 255) as u8;
+        Rgba([channel(color.0), channel(color.1), channel(color.2), alpha])
+    }));
+    return [
+        (Textures::Water, texture((40, 70, 200), 170)),
+        (Textures::Lava, texture((220, 90, 20), 255)),
+    ];
+}
+
+// green with holes that show what is behind the leaves
+fn leaves_texture() -> DynamicImage {
+    let mut rng = StdRng::seed_from_u64(5);
+    return DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |_, _| {
+        if rng.gen_bool(0.25) {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let shade = rng.gen_range(0..40);
+        Rgba([40 + shade, 110 + shade, 30 + shade, 255])
+    }));
+}
+
+// blades of different heights growing from the bottom
+fn grass_texture() -> DynamicImage {
+    let mut rng = StdRng::seed_from_u64(6);
+    let heights: Vec<u32> = (0..16).map(|x| if x % 2 == 0 { rng.gen_range(4..15) } else { 0 }).collect();
+    return DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        if 16 - y > heights[x as usize] {
+            return Rgba([0, 0, 0, 0]);
+        }
+        let shade = (y * 4) as u8;
+        Rgba([50 + shade, 120 + shade, 35 + shade, 255])
+    }));
+}
+
+// the tools every tier has
+const TOOLS: [(ToolType, &str); 3] = [(ToolType::Pickaxe, "pickaxe"), (ToolType::Axe, "axe"), (ToolType::Shovel, "shovel")];
+
+fn tool_name(kind: ToolType, tier: ToolTier) -> String {
+    let (_, name) = TOOLS.iter().find(|(x, _)| *x == kind).unwrap();
+    return format!("{} {}", tier.material(), name);
+}
+
+// a stick with a head in the tier's color
+fn tool_texture(kind: ToolType, tier: ToolTier) -> DynamicImage {
+    let (r, g, b) = match tier {
+        ToolTier::Wood => (150, 110, 60),
+        ToolTier::Gold => (240, 200, 50),
+        ToolTier::Stone => (130, 130, 130),
+        ToolTier::Iron => (215, 215, 215),
+        ToolTier::Diamond => (80, 220, 210),
+    };
+    return DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        let head = match kind {
+            ToolType::Pickaxe => (2..14).contains(&x) && (2..4).contains(&y) || !(4..12).contains(&x) && y == 4,
+            ToolType::Axe => (9..14).contains(&x) && (2..8).contains(&y),
+            _ => (6..10).contains(&x) && (1..7).contains(&y),
+        };
+        if head {
+            Rgba([r, g, b, 255])
+        } else if (7..9).contains(&x) && (2..15).contains(&y) {
+            Rgba([110, 75, 40, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    }));
+}
+
+// a few black lumps
+fn coal_texture() -> DynamicImage {
+    let lumps = [(5.0, 6.0, 3.5), (10.0, 9.0, 4.0), (6.0, 11.0, 2.5)];
+    return DynamicImage::ImageRgba8(RgbaImage::from_fn(16, 16, |x, y| {
+        let inside = lumps.iter().any(|(cx, cy, r)| (x as f32 - cx).powi(2) + (y as f32 - cy).powi(2) < r * r);
+        if inside {
+            let shine = if (x + y) % 5 == 0 { 40 } else { 0 };
+            Rgba([30 + shine, 30 + shine, 35 + shine, 255])
+        } else {
+            Rgba([0, 0, 0, 0])
+        }
+    }));
+}
+
 fn main() {
     let mut window = Window::create(640, 320, "minecraft_clone", WindowMode::Windowed, GameData {
         player: Player::new(),
         keys: Keys::new(),
         sensitivity: 0.003,
+        render_distance: 8,
         world: World::new(),
+        sky: Sky::new(0),
+        target: None,
+        use_cooldown: 0,
+        mining: Mining::new(),
     });
 
     unsafe {
         gl::Enable(gl::CULL_FACE);
         gl::CullFace(gl::BACK);
         gl::FrontFace(gl::CCW); // Counter-clockwise
+        gl::Enable(gl::DEPTH_TEST);
     }
 
     // sets up window
 
     window.set_min_size(640, 320);
     window.set_max_fps(60.0);
+    window.set_tick_rate(TICKS_PER_SECOND);
     window.set_update(update);
     window.set_render(render);
     window.set_on_event(on_event);
+    window.camera.far = (window.data.render_distance * 16) as f32;
 
     // creates shaders
 
     window.shaders.set_vertex_shader(files::load_file("./shaders/vertex_shader.glsl", &DEV).unwrap().as_str());
     window.shaders.set_fragment_shader(files::load_file("./shaders/fragment_shader.glsl", &DEV).unwrap().as_str());
     window.shaders.compile_shaders();
+
+    window.sky_shaders.set_vertex_shader(files::load_file("./shaders/sky_vertex_shader.glsl", &DEV).unwrap().as_str());
+    window.sky_shaders.set_fragment_shader(files::load_file("./shaders/sky_fragment_shader.glsl", &DEV).unwrap().as_str());
+    window.sky_shaders.compile_shaders();
    
     // creates textures
 
     window.shaders.reg_texture(Textures::GrassBlockTop, files::load_texture("./textures/grass_block_top.png", &DEV).unwrap());
     window.shaders.reg_texture(Textures::GrassBlockSide, files::load_texture("./textures/grass_block_side.png",&DEV).unwrap());
     window.shaders.reg_texture(Textures::DirtBlock, files::load_texture("./textures/dirt_block.png", &DEV).unwrap());
+    for (stage, texture) in destroy_stage_textures().into_iter().enumerate() {
+        window.shaders.reg_texture(Textures::DestroyStage(stage as u32), texture);
+    }
+    for (name, texture) in furnace_textures() {
+        window.shaders.reg_texture(name, texture);
+    }
+    window.shaders.reg_texture(Textures::Coal, coal_texture());
+    for tier in ToolTier::ALL {
+        for (kind, _) in TOOLS {
+            window.shaders.reg_texture(Textures::Tool(kind, tier), tool_texture(kind, tier));
+        }
+    }
+    for (name, texture) in chest_textures() {
+        window.shaders.reg_texture(name, texture);
+    }
+    for (name, texture) in crafting_table_textures() {
+        window.shaders.reg_texture(name, texture);
+    }
+    for (name, texture) in liquid_textures() {
+        window.shaders.reg_texture(name, texture);
+    }
+    window.shaders.reg_texture(Textures::OakLeaves, leaves_texture());
+    window.shaders.reg_texture(Textures::Grass, grass_texture());
     window.shaders.build_atlas();
 
     // creates blocks
@@ -106,6 +389,7 @@ fn main() {
         collision_data: CollisionData::Normal(1.0, 1.0, 1.0),
         model: ModelType::Block(BlockModelType {
             block_size: (1.0, 1.0, 1.0), 
+            offset: (0.0, 0.0, 0.0),
             texture: BlockTextureType::Log(LogTextureMap {
                 top: Box::new(Textures::GrassBlockTop),
                 side: Box::new(Textures::GrassBlockSide),
@@ -118,6 +402,16 @@ fn main() {
         update: None,
         random_tick: None,
         block_type: BlockType::Solid,
+        light_emission: (0, 0, 0),
+        light_opacity: 15,
+        light_filter: (0, 0, 0),
+        fog: None,
+        hardness: 0.6,
+        preferred_tool: Some(ToolType::Shovel),
+        required_tier: None,
+        on_use: None,
+        block_entity: None,
+        loot_table: Some("minecraft:blocks/grass_block".to_string()),
     });
 
     window.data.world.reg_block(BlockData {
@@ -125,6 +419,7 @@ fn main() {
         collision_data: CollisionData::Normal(1.0, 1.0, 1.0),
         model: ModelType::Block(BlockModelType {
             block_size:(1.0, 1.0, 1.0),
+            offset: (0.0, 0.0, 0.0),
             texture: BlockTextureType::All(Box::new(Textures::DirtBlock)),
         }),
         rotate: false,
@@ -133,15 +428,290 @@ fn main() {
         update: None,
         random_tick: None,
         block_type: BlockType::Solid,
+        light_emission: (0, 0, 0),
+        light_opacity: 15,
+        light_filter: (0, 0, 0),
+        fog: None,
+        hardness: 0.5,
+        preferred_tool: Some(ToolType::Shovel),
+        required_tier: None,
+        on_use: None,
+        block_entity: None,
+        loot_table: Some("minecraft:blocks/dirt_block".to_string()),
+    });
+
+    // a lit furnace still drops a furnace
+    for (name, front, light) in [(furnace::FURNACE, Textures::FurnaceFront, 0), (furnace::LIT_FURNACE, Textures::FurnaceFrontLit, 13)] {
+        window.data.world.reg_block(BlockData {
+            name: name.to_string(),
+            collision_data: CollisionData::Normal(1.0, 1.0, 1.0),
+            model: ModelType::Block(BlockModelType {
+                block_size: (1.0, 1.0, 1.0),
+                offset: (0.0, 0.0, 0.0),
+                texture: BlockTextureType::Each(TextureMap {
+                    top: Box::new(Textures::FurnaceTop),
+                    bottom: Box::new(Textures::FurnaceTop),
+                    left: Box::new(Textures::FurnaceSide),
+                    right: Box::new(Textures::FurnaceSide),
+                    front: Box::new(front),
+                    back: Box::new(Textures::FurnaceSide),
+                }),
+            }),
+            rotate: false,
+            tick: None,
+            start: None,
+            update: None,
+            random_tick: None,
+            block_type: BlockType::Solid,
+            light_emission: (light, light, light),
+            light_opacity: 15,
+            light_filter: (0, 0, 0),
+            fog: None,
+            hardness: 3.5,
+            preferred_tool: Some(ToolType::Pickaxe),
+            required_tier: Some(ToolTier::Wood),
+            on_use: Some(container::open_menu),
+            block_entity: Some(furnace::Furnace::create),
+            loot_table: Some("minecraft:blocks/furnace".to_string()),
+        });
+    }
+
+    window.data.world.reg_block(BlockData {
+        name: chest::CHEST.to_string(),
+        collision_data: CollisionData::Normal(1.0, 1.0, 1.0),
+        model: ModelType::Block(BlockModelType {
+            block_size: (1.0, 1.0, 1.0),
+            offset: (0.0, 0.0, 0.0),
+            texture: BlockTextureType::Each(TextureMap {
+                top: Box::new(Textures::ChestTop),
+                bottom: Box::new(Textures::ChestTop),
+                left: Box::new(Textures::ChestSide),
+                right: Box::new(Textures::ChestSide),
+                front: Box::new(Textures::ChestFront),
+                back: Box::new(Textures::ChestSide),
+            }),
+        }),
+        rotate: false,
+        tick: None,
+        start: None,
+        update: None,
+        random_tick: None,
+        block_type: BlockType::Solid,
+        light_emission: (0, 0, 0),
+        light_opacity: 15,
+        light_filter: (0, 0, 0),
+        fog: None,
+        hardness: 2.5,
+        preferred_tool: Some(ToolType::Axe),
+        required_tier: None,
+        on_use: Some(container::open_menu),
+        block_entity: Some(chest::Chest::create),
+        loot_table: Some("minecraft:blocks/chest".to_string()),
+    });
+
+    window.data.world.reg_block(BlockData {
+        name: crafting::CRAFTING_TABLE.to_string(),
+        collision_data: CollisionData::Normal(1.0, 1.0, 1.0),
+        model: ModelType::Block(BlockModelType {
+            block_size: (1.0, 1.0, 1.0),
+            offset: (0.0, 0.0, 0.0),
+            texture: BlockTextureType::Log(LogTextureMap {
+                top: Box::new(Textures::CraftingTableTop),
+                side: Box::new(Textures::CraftingTableSide),
+                bottom: Box::new(Textures::CraftingTableSide),
+            }),
+        }),
+        rotate: false,
+        tick: None,
+        start: None,
+        update: None,
+        random_tick: None,
+        block_type: BlockType::Solid,
+        light_emission: (0, 0, 0),
+        light_opacity: 15,
+        light_filter: (0, 0, 0),
+        fog: None,
+        hardness: 2.5,
+        preferred_tool: Some(ToolType::Axe),
+        required_tier: None,
+        on_use: Some(crafting::open_crafting_table),
+        block_entity: None,
+        loot_table: Some("minecraft:blocks/crafting_table".to_string()),
+    });
+
+    // drawn with the opaque blocks, the holes in the texture are cut out
+    window.data.world.reg_block(BlockData {
+        name: "oak leaves".to_string(),
+        collision_data: CollisionData::Normal(1.0, 1.0, 1.0),
+        model: ModelType::Block(BlockModelType {
+            block_size: (1.0, 1.0, 1.0),
+            offset: (0.0, 0.0, 0.0),
+            texture: BlockTextureType::All(Box::new(Textures::OakLeaves)),
+        }),
+        rotate: false,
+        tick: None,
+        start: None,
+        update: None,
+        random_tick: None,
+        block_type: BlockType::Cutout,
+        light_emission: (0, 0, 0),
+        light_opacity: 1,
+        light_filter: (0, 0, 0),
+        fog: None,
+        hardness: 0.2,
+        preferred_tool: Some(ToolType::Hoe),
+        required_tier: None,
+        on_use: None,
+        block_entity: None,
+        loot_table: None,
+    });
+
+    // crossed quads that can be walked through and broken instantly
+    window.data.world.reg_block(BlockData {
+        name: "grass".to_string(),
+        collision_data: CollisionData::Interact(0.75, 0.8, 0.75),
+        model: ModelType::Plant(PlantModelType {
+            texture: Box::new(Textures::Grass),
+            random_offset: true,
+        }),
+        rotate: false,
+        tick: None,
+        start: None,
+        update: None,
+        random_tick: None,
+        block_type: BlockType::Cutout,
+        light_emission: (0, 0, 0),
+        light_opacity: 0,
+        light_filter: (0, 0, 0),
+        fog: None,
+        hardness: 0.0,
+        preferred_tool: None,
+        required_tier: None,
+        on_use: None,
+        block_entity: None,
+        loot_table: None,
     });
+
+    // liquids can't be walked on or aimed at, so they can't be broken and other blocks
+    // can be placed into them, and they don't hide or shade the blocks around them
+    for (name, texture, light, opacity, fog) in [("water", Textures::Water, 0, 2, Fog::water()), ("lava", Textures::Lava, 15, 15, Fog::lava())] {
+        window.data.world.reg_block(BlockData {
+            name: name.to_string(),
+            collision_data: CollisionData::None,
+            model: ModelType::Block(BlockModelType {
+                block_size: (1.0, 1.0, 1.0),
+                offset: (0.0, 0.0, 0.0),
+                texture: BlockTextureType::All(Box::new(texture)),
+            }),
+            rotate: false,
+            tick: None,
+            start: None,
+            update: None,
+            random_tick: None,
+            block_type: BlockType::Transparent,
+            light_emission: (light, light, light),
+            light_opacity: opacity,
+            light_filter: (0, 0, 0),
+            fog: Some(fog),
+            hardness: 100.0,
+            preferred_tool: None,
+            required_tier: None,
+            on_use: None,
+            block_entity: None,
+            loot_table: None,
+        });
+    }
+
+    // creates items
+
+    let mut coal = ItemData::new("coal", 64);
+    coal.burn_time = 1600;
+    coal.texture = Some(Box::new(Textures::Coal));
+    window.data.world.reg_item(coal);
+
+    for tier in ToolTier::ALL {
+        for (kind, _) in TOOLS {
+            let mut tool = ItemData::new(&tool_name(kind, tier), 1);
+            tool.tool = Some(Tool {
+                kind,
+                tier,
+            });
+            tool.texture = Some(Box::new(Textures::Tool(kind, tier)));
+            window.data.world.reg_item(tool);
+        }
+    }
+
+    // loads data, tags first since recipes and loot tables use them
+
+    crafting::load_item_tags(&mut window.data.world, &DEV);
+    crafting::load_recipes(&mut window.data.world, &DEV);
+    loot::load_loot_tables(&mut window.data.world, &DEV);
+
+    // gives the player something to build with, the lit furnace only comes from lighting one
+    for block in 1..window.data.world.blocks.len() as u64 {
+        let world = &window.data.world;
+        if world.blocks[block as usize].name == furnace::LIT_FURNACE {
+            continue;
+        }
+        if let Some(item) = world.block_item(block) {
+            window.data.player.inventory.add(world.item_stack(item, 64));
+        }
+    }
+    if let Some(coal) = window.data.world.find_item("coal") {
+        window.data.player.inventory.add(window.data.world.item_stack(coal, 64));
+    }
+    // and every tool no recipe makes, so there is no other way to get it
+    for item in 1..window.data.world.items.len() as u64 {
+        let world = &window.data.world;
+        if world.items[item as usize].tool.is_some() && world.recipes.for_result(item).is_empty() {
+            window.data.player.inventory.add(world.item_stack(item, 1));
+        }
+    }
+
     //starts window
    
-    window.data.world.place_block(Vec3::new(0.0, 0.0, 0.0), Block::new(1, NbtBlock::new(), &window.data.world));
+    let save = files::save_path(SAVE_FILE).unwrap();
+    // a save that didn't load is copied aside before the new world is saved over it,
+    // and kept as it is if that fails too
+    let mut may_save = true;
+    if std::path::Path::new(&save).exists() {
+        if let Err(e) = window.data.world.load(&save) {
+            eprintln!("failed to load {}: {}", save, e);
+            let backup = format!("{}.bak", save);
+            match std::fs::copy(&save, &backup) {
+                Ok(_) => eprintln!("kept the old save as {}", backup),
+                Err(e) => {
+                    eprintln!("failed to back up {}: {}, the world won't be saved", save, e);
+                    may_save = false;
+                }
+            }
+        }
+    } else {
+        let world = &mut window.data.world;
+        world.place_block(Vec3::new(0.0, 0.0, 0.0), Block::new(1, NbtBlock::new(), world));
+        // with a chest next to it that fills up when it is first opened
+        if let Some(chest) = world.find_block(chest::CHEST) {
+            let mut nbt = NbtBlock::new();
+            nbt.data.set("loot_table", nbt::Tag::String(STARTER_LOOT.to_string()));
+            world.place_block(Vec3::new(1.0, 0.0, 0.0), Block::new(chest, nbt, world));
+        }
+    }
 
     window.start();
+
+    if !may_save {
+        return;
+    }
+    if let Err(e) = window.data.world.save(&save) {
+        eprintln!("failed to save {}: {}", save, e);
+    }
 }
 
 fn on_event(window: &mut Window<GameData>, event: Event) {
+    if window.data.player.menu.is_some() {
+        menu_event(window, event);
+        return;
+    }
     match event {
         Event::Key(Key::W, _, action, _) => {
             if action == Action::Repeat {return;}
@@ -167,6 +737,32 @@ fn on_event(window: &mut Window<GameData>, event: Event) {
             if action == Action::Repeat {return;}
             window.data.keys.shift = action == Action::Press;
         }
+        Event::Key(Key::LeftControl, _, action, _) => {
+            if action == Action::Repeat {return;}
+            window.data.keys.control = action == Action::Press;
+        }
+        Event::MouseButton(MouseButton::Button1, action, _) => {
+            window.data.keys.left_mouse = action == Action::Press;
+            // clicking always acts straight away, only holding waits
+            window.data.use_cooldown = 0;
+        }
+        Event::MouseButton(MouseButton::Button2, action, _) => {
+            window.data.keys.right_mouse = action == Action::Press;
+            window.data.use_cooldown = 0;
+        }
+        Event::Key(key, _, Action::Press, _) if (Key::Num1 as i32..=Key::Num9 as i32).contains(&(key as i32)) => {
+            window.data.player.inventory.select((key as i32 - Key::Num1 as i32) as usize);
+        }
+        Event::Scroll(_, y) if y != 0.0 => {
+            window.data.player.inventory.scroll(-y.signum() as i32);
+        }
+        Event::Key(Key::E, _, Action::Press, _) => {
+            window.data.player.menu = Some(crafting::inventory_menu());
+        }
+        Event::Key(Key::F4, _, Action::Press, _) => {
+            let mode = window.data.player.game_mode.next();
+            window.data.player.set_game_mode(mode);
+        }
         Event::Key(Key::Escape, _, Action::Press, _) => {
             if window.get_cursor_mode() == CursorMode::Disabled {
                 window.set_cursor_mode(CursorMode::Normal);
@@ -187,45 +783,250 @@ fn on_event(window: &mut Window<GameData>, event: Event) {
     }    
 }
 
+// clicking and dragging items around the open menu
+fn menu_event(window: &mut Window<GameData>, event: Event) {
+    if let Event::Key(Key::Escape | Key::E, _, Action::Press, _) = event {
+        close_menu(window);
+        return;
+    }
+    let (width, height) = window.get_resolution();
+    let cursor = window.get_cursor_pos();
+    let data = &mut window.data;
+    let Some(menu) = &mut data.player.menu else {
+        return;
+    };
+    let slot = hud::menu_slot_at(menu, cursor.x, cursor.y, width as f32, height as f32);
+    let inventory = &mut data.player.inventory;
+    let took = match event {
+        Event::MouseButton(button @ (MouseButton::Button1 | MouseButton::Button2), Action::Press, modifiers) => {
+            let right = button == MouseButton::Button2;
+            let shift = modifiers.contains(Modifiers::Shift);
+            menu.with_inventories(inventory, &mut data.world, |menu, inventories, world| menu.press(slot, right, shift, inventories, world))
+        }
+        Event::MouseButton(MouseButton::Button1 | MouseButton::Button2, Action::Release, _) => {
+            menu.with_inventories(inventory, &mut data.world, |menu, inventories, world| menu.release(slot, inventories, world))
+        }
+        Event::CursorPos(_, _) => {
+            if let (Some(slot), true) = (slot, menu.is_dragging()) {
+                menu.with_inventories(inventory, &mut data.world, |menu, inventories, world| menu.drag_over(slot, inventories, world));
+            }
+            false
+        }
+        _ => false,
+    };
+    // taking a furnace's results gives its experience, and taking a crafting result uses
+    // up the ingredients
+    if took {
+        match slot.map(|x| menu.containers[menu.slots[x].container]) {
+            Some(container::ContainerRef::Block(pos)) => {
+                if let Some(entity) = data.world.block_entity_mut(pos) {
+                    entity.output_taken(&mut data.player);
+                }
+            }
+            Some(container::ContainerRef::CraftingResult) => menu.crafted(&data.world),
+            _ => {}
+        }
+    }
+}
+
+// gives back what the cursor held and goes back to looking around
+fn close_menu(window: &mut Window<GameData>) {
+    let Some(mut menu) = window.data.player.menu.take() else {
+        return;
+    };
+    // what doesn't fit back in the inventory is dropped
+    for stack in menu.close() {
+        if let Some(leftover) = window.data.player.inventory.add(stack) {
+            let pos = window.data.player.eye_pos();
+            window.data.world.spawn_item(pos, leftover);
+        }
+    }
+    window.set_cursor_mode(CursorMode::Disabled);
+    window.set_cursor_pos(Vec2::new(0.0, 0.0));
+}
+
 fn update(window: &mut Window<GameData>) {
-    let mut move_vec = Vec3::new(0.0, 0.0, 0.0);
-    if window.data.keys.w {
-       move_vec.z += 1.0;
+    window.data.world.tick();
+    let player = &window.data.player;
+    if player.menu.as_ref().is_some_and(|menu| !menu.still_valid(&window.data.world, player)) {
+        close_menu(window);
     }
-    if window.data.keys.s {
-        move_vec.z -= 1.0;
+
+    let keys = &window.data.keys;
+    let mut input = MoveInput::default();
+    if keys.w {
+        input.forward += 1.0;
     }
-    if window.data.keys.a {
-        move_vec.x -= 1.0;
+    if keys.s {
+        input.forward -= 1.0;
     }
-    if window.data.keys.d {
-        move_vec.x += 1.0;
+    if keys.a {
+        input.strafe -= 1.0;
     }
-    if move_vec != Vec3::new(0.0, 0.0, 0.0) {
-        move_vec = move_vec.normalize() * window.data.player.speed;
-        rotate(&mut move_vec, &window.camera.rotation); 
-        window.camera.pos = window.camera.pos + move_vec;
+    if keys.d {
+        input.strafe += 1.0;
     }
-    if window.data.keys.space {
-        window.camera.pos.y += window.data.player.speed;
+    input.jump = keys.space;
+    input.sneak = keys.shift;
+    input.sprint = keys.control;
+
+    let yaw = window.camera.rotation.x;
+    window.data.player.tick(&window.data.world, input, yaw);
+    // spectators can't touch anything, items included
+    let player = &mut window.data.player;
+    if player.game_mode.abilities().may_interact {
+        let (x, y, z) = PICKUP_REACH;
+        window.data.world.pick_up_items(&player.aabb().inflate(Vec3::new(x, y, z)), &mut player.inventory);
     }
-    if window.data.keys.shift {
-        window.camera.pos.y -= window.data.player.speed;
+    use_blocks(window);
+    // a block or the inventory key just opened a menu, the mouse is needed to use it
+    if window.data.player.menu.is_some() && window.get_cursor_mode() == CursorMode::Disabled {
+        window.data.keys = Keys::new();
+        window.set_cursor_mode(CursorMode::Normal);
+        let (width, height) = window.get_resolution();
+        window.set_cursor_pos(Vec2::new(width as f32 / 2.0, height as f32 / 2.0));
     }
+
+    window.fog = current_fog(window);
+    let clear_color = window.fog.color;
+    window.set_clear_color(clear_color);
 }
 
-fn render(window: &mut Window<GameData>) {
+fn render(window: &mut Window<GameData>, alpha: f32) {
+    window.camera.pos = window.data.player.eye_pos_at(alpha);
+    let reach = window.data.player.game_mode.abilities().reach;
+    window.data.target = raycast(&window.data.world, window.camera.pos, window.camera.forward(), reach);
+
+    // inside blocks with their own fog there is no sky to see
+    if block_fog(window).is_none() {
+        let (sky, clouds) = window.data.sky.build(&window.data.world, &window.camera);
+        window.render_sky(&sky, &clouds);
+    }
+
     let mut verts: Vec<f32> = Vec::new();
-    window.data.world.render(&mut verts, Vec3::new(0.0, 0.0, 0.0), window);
+    let mut translucent: Vec<f32> = Vec::new();
+    window.data.world.render(&mut verts, &mut translucent, window.camera.pos, window.data.render_distance, window);
+    window.data.world.render_item_entities(&mut verts, alpha, window);
+    window.shaders.use_program();
+    window.shaders.set_uniform_float("u_SkyBrightness", window.data.world.sky_brightness());
     window.render_triangles(&verts);
+    render_target(window);
+    window.render_translucent_triangles(&translucent);
+
+    let (width, height) = window.get_resolution();
+    let (colored, icons) = hud::build(&window.data.player, &window.data.world, window, width as f32, height as f32);
+    window.render_hud(&colored, &icons);
 }
 
-fn rotate(vec: &mut Vec3, rotation: &Vec2) {
-    let mut view = glm::identity();
+// outlines the block the player is looking at and cracks it as it is mined
+fn render_target(window: &Window<GameData>) {
+    if let Some((pos, progress)) = window.data.mining.progress() {
+        let stage = ((progress * DESTROY_STAGES as f32) as u32).min(DESTROY_STAGES - 1);
+        let mut cracks = Vec::new();
+        window.data.world.render_cracks(&mut cracks, pos.to_vec(), &window.shaders.get_texture(Textures::DestroyStage(stage)));
+        window.render_translucent_triangles(&cracks);
+    }
+    if let Some(target) = &window.data.target {
+        let Some(block) = window.data.world.block_at(target.pos.to_vec()) else {
+            return;
+        };
+        let mut lines = Vec::new();
+        for aabb in block.collision_data.outline_shape().boxes_at(target.pos.to_vec()) {
+            let grow = Vec3::new(0.002, 0.002, 0.002);
+            box_lines(&mut lines, aabb.min - grow, aabb.max + grow, OUTLINE_COLOR);
+        }
+        window.render_lines(&lines);
+    }
+}
+
+// breaks or places the block the player is looking at while the mouse buttons are held
+fn use_blocks(window: &mut Window<GameData>) {
+    window.data.use_cooldown = window.data.use_cooldown.saturating_sub(1);
+    let player = &window.data.player;
+    let keys = &window.data.keys;
+    let abilities = player.game_mode.abilities();
+    let hit = raycast(&window.data.world, player.eye_pos(), window.camera.forward(), abilities.reach);
+    // mining starts over when letting go, and when looking at another block in Mining::tick
+    if !keys.left_mouse || hit.is_none() || !abilities.may_interact {
+        window.data.mining.cancel();
+    }
+    if !abilities.may_interact || window.data.use_cooldown > 0 || !(keys.left_mouse || keys.right_mouse) {
+        return;
+    }
+    let Some(hit) = hit else {
+        return;
+    };
+
+    if keys.left_mouse {
+        let tool = player.held_tool(&window.data.world);
+        if let Some(pos) = window.data.mining.tick(&window.data.world, hit.pos, tool, abilities.instant_break) {
+            break_block(window, pos, tool);
+            window.data.use_cooldown = BREAK_DELAY;
+        }
+    } else if keys.right_mouse {
+        window.data.use_cooldown = PLACE_DELAY;
+        // sneaking places against blocks instead of using them
+        let sneaking = player.sneaking;
+        if !sneaking && window.data.world.use_block(hit.pos.to_vec(), &mut window.data.player, &hit) {
+            return;
+        }
+        let player = &window.data.player;
+        let Some(block) = player.inventory.selected_stack().and_then(|stack| window.data.world.items[stack.item as usize].block) else {
+            return;
+        };
+        let pos = hit.pos.offset(hit.face);
+        if can_place(window, pos, block) {
+            window.data.world.place_block(pos.to_vec(), Block::new(block, NbtBlock::new(), &window.data.world));
+            if !abilities.infinite_blocks {
+                let selected = window.data.player.inventory.selected;
+                window.data.player.inventory.remove(selected, 1);
+            }
+        }
+    }
+}
 
-    view = glm::rotate_y(&view, rotation.x);
-    
-    let new_vec = view * glm::vec3_to_vec4(vec);
-    *vec = new_vec.xyz();
+// replaces the block with air, in survival its loot drops when the tool can harvest it,
+// and anything it held always does
+fn break_block(window: &mut Window<GameData>, pos: BlockPos, tool: Option<Tool>) {
+    let world = &mut window.data.world;
+    let Some(id) = world.block_at(pos.to_vec()).map(|block| block.get_id()) else {
+        return;
+    };
+    world.unpack_loot(pos);
+    let mut drops: Vec<ItemStack> = world.block_entity(pos).and_then(|x| x.inventory()).map_or(Vec::new(), |x| x.slots.iter().flatten().cloned().collect());
+    world.place_block(pos.to_vec(), Block::new(0, NbtBlock::new(), world));
+    let player = &window.data.player;
+    let harvested = player.game_mode.abilities().drops && can_harvest(&world.blocks[id as usize], tool);
+    if let Some(table) = world.blocks[id as usize].loot_table.clone().filter(|_| harvested) {
+        let context = loot::LootContext {
+            tool: player.inventory.selected_stack(),
+        };
+        drops.extend(world.roll_loot(&table, &context));
+    }
+    for stack in drops {
+        world.spawn_item(pos.to_vec(), stack);
+    }
 }
 
+// blocks can only go into air or liquids inside the world and not where they would trap the player
+fn can_place(window: &Window<GameData>, pos: BlockPos, id: u64) -> bool {
+    let world = &window.data.world;
+    match world.block_at(pos.to_vec()) {
+        Some(block) if block.collision_data.outline_shape().is_empty() => {}
+        _ => return false,
+    }
+    let player = window.data.player.aabb();
+    let shape = world.blocks[id as usize].collision_data.collision_shape();
+    return !shape.boxes_at(pos.to_vec()).iter().any(|b| b.intersects(&player));
+}
+
+// fog of the block the player's eyes are in, like water or lava
+fn block_fog(window: &Window<GameData>) -> Option<Fog> {
+    let world = &window.data.world;
+    let block = world.block_at(window.data.player.eye_pos().round())?;
+    return world.blocks[block.get_id() as usize].fog;
+}
+
+fn current_fog(window: &Window<GameData>) -> Fog {
+    return block_fog(window).unwrap_or(Fog::distance(window.data.world.fog_color(), window.camera.far));
+}
@@ -0,0 +1,86 @@
+use crate::items::*;
+use crate::world::*;
+
+// breaking a block takes its hardness times this many ticks with the right tool, or
+// the slower rate without it
+const HARVEST_TICKS: f32 = 30.0;
+const NO_HARVEST_TICKS: f32 = 100.0;
+
+pub enum MiningState {
+    Idle,
+    Breaking {
+        pos: BlockPos,
+        progress: f32, // 0 to 1
+    },
+}
+
+pub struct Mining {
+    pub state: MiningState,
+}
+
+impl Mining {
+    pub fn new() -> Self {
+        return Self {
+            state: MiningState::Idle,
+        }
+    }
+    // the block being broken and how far along it is
+    pub fn progress(&self) -> Option<(BlockPos, f32)> {
+        match self.state {
+            MiningState::Idle => None,
+            MiningState::Breaking { pos, progress } => Some((pos, progress)),
+        }
+    }
+    pub fn cancel(&mut self) {
+        self.state = MiningState::Idle;
+    }
+    // mines the target for one tick, starting over when it changes, and returns the
+    // block once it breaks
+    pub fn tick(&mut self, world: &World, target: BlockPos, tool: Option<Tool>, instant: bool) -> Option<BlockPos> {
+        let progress = match self.state {
+            MiningState::Breaking { pos, progress } if pos == target => progress,
+            _ => 0.0,
+        };
+        let block = world.block_at(target.to_vec())?;
+        let speed = if instant {
+            1.0
+        } else {
+            break_speed(&world.blocks[block.get_id() as usize], tool)
+        };
+        let progress = progress + speed;
+        // adding up the speed can fall just short of 1 on the last tick
+        if progress >= 1.0 - 1e-4 {
+            self.state = MiningState::Idle;
+            return Some(target);
+        }
+        self.state = MiningState::Breaking {
+            pos: target,
+            progress,
+        };
+        return None;
+    }
+}
+
+// whether the block drops anything when broken with tool
+pub fn can_harvest(block: &BlockData, tool: Option<Tool>) -> bool {
+    let Some(tier) = block.required_tier else {
+        return true;
+    };
+    return tool.is_some_and(|tool| Some(tool.kind) == block.preferred_tool && tool.tier.level() >= tier.level());
+}
+
+// part of the block broken each tick
+pub fn break_speed(block: &BlockData, tool: Option<Tool>) -> f32 {
+    if block.hardness < 0.0 {
+        return 0.0;
+    }
+    if block.hardness == 0.0 {
+        return 1.0;
+    }
+    let speed = match tool {
+        Some(tool) if Some(tool.kind) == block.preferred_tool => tool.tier.speed(),
+        _ => 1.0,
+    };
+    let ticks = if can_harvest(block, tool) { HARVEST_TICKS } else { NO_HARVEST_TICKS };
+    return speed / block.hardness / ticks;
+}
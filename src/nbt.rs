@@ -0,0 +1,169 @@
+use std::{collections::BTreeMap, io};
+
+// named binary tags, extra data that items, blocks and entities can carry around
+#[derive(Clone, PartialEq, Debug)]
+pub enum Tag {
+    Byte(i8),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Compound),
+}
+
+// tags by name, kept sorted so equal compounds compare and save the same
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Compound {
+    tags: BTreeMap<String, Tag>,
+}
+
+impl Compound {
+    pub fn new() -> Self {
+        return Self {
+            tags: BTreeMap::new(),
+        }
+    }
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        return self.tags.get(name);
+    }
+    pub fn set(&mut self, name: &str, tag: Tag) {
+        self.tags.insert(name.to_string(), tag);
+    }
+    pub fn remove(&mut self, name: &str) -> Option<Tag> {
+        return self.tags.remove(name);
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.tags.is_empty();
+    }
+    pub fn get_int(&self, name: &str) -> Option<i32> {
+        match self.get(name)? {
+            Tag::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+    pub fn get_float(&self, name: &str) -> Option<f32> {
+        match self.get(name)? {
+            Tag::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+    pub fn get_string(&self, name: &str) -> Option<&str> {
+        match self.get(name)? {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn get_compound(&self, name: &str) -> Option<&Compound> {
+        match self.get(name)? {
+            Tag::Compound(value) => Some(value),
+            _ => None,
+        }
+    }
+    // every tag as its type, name and value, ended by a zero type
+    pub fn write(&self, data: &mut Vec<u8>) {
+        for (name, tag) in self.tags.iter() {
+            data.push(tag.type_id());
+            write_string(data, name);
+            tag.write(data);
+        }
+        data.push(END);
+    }
+    pub fn read(data: &[u8], pos: &mut usize) -> io::Result<Self> {
+        let mut compound = Self::new();
+        loop {
+            let id = take(data, pos, 1)?[0];
+            if id == END {
+                return Ok(compound);
+            }
+            let name = read_string(data, pos)?;
+            compound.set(&name, Tag::read(id, data, pos)?);
+        }
+    }
+}
+
+// the same type ids as minecraft, though the numbers are little endian
+const END: u8 = 0;
+const BYTE: u8 = 1;
+const INT: u8 = 3;
+const LONG: u8 = 4;
+const FLOAT: u8 = 5;
+const DOUBLE: u8 = 6;
+const STRING: u8 = 8;
+const LIST: u8 = 9;
+const COMPOUND: u8 = 10;
+
+impl Tag {
+    fn type_id(&self) -> u8 {
+        match self {
+            Self::Byte(_) => BYTE,
+            Self::Int(_) => INT,
+            Self::Long(_) => LONG,
+            Self::Float(_) => FLOAT,
+            Self::Double(_) => DOUBLE,
+            Self::String(_) => STRING,
+            Self::List(_) => LIST,
+            Self::Compound(_) => COMPOUND,
+        }
+    }
+    fn write(&self, data: &mut Vec<u8>) {
+        match self {
+            Self::Byte(value) => data.extend_from_slice(&value.to_le_bytes()),
+            Self::Int(value) => data.extend_from_slice(&value.to_le_bytes()),
+            Self::Long(value) => data.extend_from_slice(&value.to_le_bytes()),
+            Self::Float(value) => data.extend_from_slice(&value.to_le_bytes()),
+            Self::Double(value) => data.extend_from_slice(&value.to_le_bytes()),
+            Self::String(value) => write_string(data, value),
+            // lists here can mix types so every element keeps its own
+            Self::List(values) => {
+                data.extend_from_slice(&(values.len() as u32).to_le_bytes());
+                for value in values.iter() {
+                    data.push(value.type_id());
+                    value.write(data);
+                }
+            }
+            Self::Compound(value) => value.write(data),
+        }
+    }
+    fn read(id: u8, data: &[u8], pos: &mut usize) -> io::Result<Self> {
+        return Ok(match id {
+            BYTE => Self::Byte(i8::from_le_bytes(take(data, pos, 1)?.try_into().unwrap())),
+            INT => Self::Int(i32::from_le_bytes(take(data, pos, 4)?.try_into().unwrap())),
+            LONG => Self::Long(i64::from_le_bytes(take(data, pos, 8)?.try_into().unwrap())),
+            FLOAT => Self::Float(f32::from_le_bytes(take(data, pos, 4)?.try_into().unwrap())),
+            DOUBLE => Self::Double(f64::from_le_bytes(take(data, pos, 8)?.try_into().unwrap())),
+            STRING => Self::String(read_string(data, pos)?),
+            LIST => {
+                let len = u32::from_le_bytes(take(data, pos, 4)?.try_into().unwrap());
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    let id = take(data, pos, 1)?[0];
+                    values.push(Self::read(id, data, pos)?);
+                }
+                Self::List(values)
+            }
+            COMPOUND => Self::Compound(Compound::read(data, pos)?),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown tag type {}", id))),
+        });
+    }
+}
+
+fn write_string(data: &mut Vec<u8>, value: &str) {
+    data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    data.extend_from_slice(value.as_bytes());
+}
+
+fn read_string(data: &[u8], pos: &mut usize) -> io::Result<String> {
+    let len = u32::from_le_bytes(take(data, pos, 4)?.try_into().unwrap()) as usize;
+    return String::from_utf8(take(data, pos, len)?.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+}
+
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> io::Result<&'a [u8]> {
+    if *pos + len > data.len() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "tags ended early"));
+    }
+    let slice = &data[*pos..*pos + len];
+    *pos += len;
+    return Ok(slice);
+}
@@ -0,0 +1,148 @@
+use crate::graphics::*;
+use crate::world::World;
+
+// small gap kept between boxes so resting on a surface doesn't count as inside it
+const EPSILON: f32 = 1e-5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        return Self {
+            min,
+            max,
+        }
+    }
+    // a box standing on pos, like an entity's feet
+    pub fn from_feet(pos: Vec3, width: f32, height: f32) -> Self {
+        return Self {
+            min: Vec3::new(pos.x - width / 2.0, pos.y, pos.z - width / 2.0),
+            max: Vec3::new(pos.x + width / 2.0, pos.y + height, pos.z + width / 2.0),
+        }
+    }
+    pub fn offset(&self, vec: Vec3) -> Self {
+        return Self::new(self.min + vec, self.max + vec);
+    }
+    // grows the box by amount on every side
+    pub fn inflate(&self, amount: Vec3) -> Self {
+        return Self::new(self.min - amount, self.max + amount);
+    }
+    // grows the box to cover everything it touches while moving by motion
+    pub fn expand_towards(&self, motion: Vec3) -> Self {
+        let mut this = *self;
+        for axis in 0..3 {
+            if motion[axis] < 0.0 {
+                this.min[axis] += motion[axis];
+            } else {
+                this.max[axis] += motion[axis];
+            }
+        }
+        return this;
+    }
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        for axis in 0..3 {
+            if self.max[axis] <= other.min[axis] || self.min[axis] >= other.max[axis] {
+                return false;
+            }
+        }
+        return true;
+    }
+    // how far other can move along axis before hitting this box, clamped to motion
+    pub fn clip(&self, other: &Aabb, axis: usize, motion: f32) -> f32 {
+        for a in 0..3 {
+            if a == axis {
+                continue;
+            }
+            if other.max[a] <= self.min[a] || other.min[a] >= self.max[a] {
+                return motion;
+            }
+        }
+        if motion > 0.0 && other.max[axis] <= self.min[axis] + EPSILON {
+            return motion.min(self.min[axis] - other.max[axis] - EPSILON).max(0.0);
+        }
+        if motion < 0.0 && other.min[axis] >= self.max[axis] - EPSILON {
+            return motion.max(self.max[axis] - other.min[axis] + EPSILON).min(0.0);
+        }
+        return motion;
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Collision {
+    pub on_ground: bool,
+    pub hit_ceiling: bool,
+    pub hit_x: bool,
+    pub hit_z: bool,
+}
+
+impl Collision {
+    pub fn hit_wall(&self) -> bool {
+        return self.hit_x || self.hit_z;
+    }
+}
+
+// moves the box by motion one axis at a time, y first, stopping at the world's blocks.
+// walls up to step_height tall are stepped onto instead of stopping at them, pass 0 while in the air
+pub fn move_and_collide(world: &World, aabb: Aabb, motion: Vec3, step_height: f32) -> (Vec3, Collision) {
+    let boxes = world.collision_boxes(&aabb.expand_towards(motion).expand_towards(Vec3::new(0.0, step_height, 0.0)));
+    let moved = sweep(&boxes, aabb, motion);
+    let collision = collision_of(motion, moved);
+
+    if step_height > 0.0 && collision.hit_wall() {
+        // tries again from step_height higher and then settles back down onto whatever is there
+        let up = sweep(&boxes, aabb, Vec3::new(0.0, step_height, 0.0));
+        let raised = aabb.offset(up);
+        let across = sweep(&boxes, raised, Vec3::new(motion.x, 0.0, motion.z));
+        let down = sweep(&boxes, raised.offset(across), Vec3::new(0.0, -up.y + motion.y.min(0.0), 0.0));
+        let stepped = up + across + down;
+        if horizontal_length(stepped) > horizontal_length(moved) {
+            let collision = Collision {
+                on_ground: true,
+                hit_ceiling: false,
+                hit_x: across.x != motion.x,
+                hit_z: across.z != motion.z,
+            };
+            return (stepped, collision);
+        }
+    }
+    return (moved, collision);
+}
+
+// whether the box is resting on top of a block
+pub fn is_on_ground(world: &World, aabb: Aabb) -> bool {
+    let below = aabb.offset(Vec3::new(0.0, -0.01, 0.0));
+    return world.collision_boxes(&below).iter().any(|b| b.max.y <= aabb.min.y + EPSILON * 2.0);
+}
+
+fn sweep(boxes: &[Aabb], aabb: Aabb, motion: Vec3) -> Vec3 {
+    let mut aabb = aabb;
+    let mut moved = Vec3::new(0.0, 0.0, 0.0);
+    for axis in [1, 0, 2] {
+        let mut distance = motion[axis];
+        for b in boxes.iter() {
+            distance = b.clip(&aabb, axis, distance);
+        }
+        moved[axis] = distance;
+        let mut offset = Vec3::new(0.0, 0.0, 0.0);
+        offset[axis] = distance;
+        aabb = aabb.offset(offset);
+    }
+    return moved;
+}
+
+fn collision_of(motion: Vec3, moved: Vec3) -> Collision {
+    return Collision {
+        on_ground: motion.y < 0.0 && moved.y != motion.y,
+        hit_ceiling: motion.y > 0.0 && moved.y != motion.y,
+        hit_x: moved.x != motion.x,
+        hit_z: moved.z != motion.z,
+    }
+}
+
+fn horizontal_length(vec: Vec3) -> f32 {
+    return (vec.x * vec.x + vec.z * vec.z).sqrt();
+}
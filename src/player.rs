@@ -0,0 +1,381 @@
+use nalgebra_glm as glm;
+
+use crate::container::Menu;
+use crate::graphics::*;
+use crate::inventory::*;
+use crate::items::Tool;
+use crate::physics::*;
+use crate::world::World;
+
+pub const TICKS_PER_SECOND: f64 = 20.0;
+
+// all of these are in blocks per tick
+const GRAVITY: f32 = 0.08;
+const AIR_DRAG: f32 = 0.98; // vertical speed kept each tick
+const TERMINAL_VELOCITY: f32 = 3.92;
+const JUMP_VELOCITY: f32 = 0.42;
+const SPRINT_JUMP_BOOST: f32 = 0.2;
+const GROUND_FRICTION: f32 = 0.546; // horizontal speed kept each tick on the ground
+const AIR_FRICTION: f32 = 0.91; // and in the air
+const GROUND_ACCELERATION: f32 = 0.1;
+const AIR_ACCELERATION: f32 = 0.02;
+const SPRINT_MULTIPLIER: f32 = 1.3;
+const SNEAK_MULTIPLIER: f32 = 0.3;
+const SNEAK_EYE_DROP: f32 = 0.08;
+// how far sneaking moves towards an edge at a time while looking for the last safe spot
+const SNEAK_EDGE_STEP: f32 = 0.05;
+const VOID_HEIGHT: f32 = -64.0;
+const FLY_ACCELERATION: f32 = 0.05;
+const FLY_VERTICAL_SPEED: f32 = 0.15;
+const FLY_VERTICAL_FRICTION: f32 = 0.6;
+const DOUBLE_TAP_TICKS: u32 = 7; // how soon jump has to be pressed again to start or stop flying
+const SAFE_FALL_DISTANCE: f32 = 3.0;
+pub const MAX_HEALTH: f32 = 20.0;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Spectator,
+}
+
+// what the player is allowed to do in a game mode
+#[derive(Clone, Copy)]
+pub struct Abilities {
+    pub may_fly: bool,
+    pub always_flying: bool,
+    pub no_clip: bool,
+    pub invulnerable: bool,
+    pub instant_break: bool,
+    pub infinite_blocks: bool,
+    pub drops: bool,
+    pub may_interact: bool,
+    pub shows_crosshair: bool,
+    pub shows_health: bool,
+    pub shows_hotbar: bool,
+    pub reach: f32, // how far away blocks can be targeted
+}
+
+impl GameMode {
+    pub fn abilities(&self) -> Abilities {
+        match self {
+            Self::Survival => Abilities {
+                may_fly: false,
+                always_flying: false,
+                no_clip: false,
+                invulnerable: false,
+                instant_break: false,
+                infinite_blocks: false,
+                drops: true,
+                may_interact: true,
+                shows_crosshair: true,
+                shows_health: true,
+                shows_hotbar: true,
+                reach: 4.5,
+            },
+            Self::Creative => Abilities {
+                may_fly: true,
+                always_flying: false,
+                no_clip: false,
+                invulnerable: true,
+                instant_break: true,
+                infinite_blocks: true,
+                drops: false,
+                may_interact: true,
+                shows_crosshair: true,
+                shows_health: false,
+                shows_hotbar: true,
+                reach: 5.0,
+            },
+            Self::Spectator => Abilities {
+                may_fly: true,
+                always_flying: true,
+                no_clip: true,
+                invulnerable: true,
+                instant_break: false,
+                infinite_blocks: false,
+                drops: false,
+                may_interact: false,
+                shows_crosshair: false,
+                shows_health: false,
+                shows_hotbar: false,
+                reach: 5.0,
+            },
+        }
+    }
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Survival => Self::Creative,
+            Self::Creative => Self::Spectator,
+            Self::Spectator => Self::Survival,
+        }
+    }
+}
+
+// what the player is trying to do this tick
+#[derive(Clone, Copy, Default)]
+pub struct MoveInput {
+    pub forward: f32, // -1 to 1
+    pub strafe: f32, // -1 to 1, positive is right
+    pub jump: bool,
+    pub sneak: bool,
+    pub sprint: bool,
+}
+
+pub struct Player {
+    pub pos: Vec3, // feet
+    pub prev_pos: Vec3, // where the feet were last tick, for drawing in between ticks
+    pub velocity: Vec3, // blocks per tick
+    pub spawn: Vec3,
+    pub width: f32,
+    pub height: f32,
+    pub eye_height: f32,
+    pub step_height: f32,
+    pub collision: Collision,
+    pub sneaking: bool,
+    pub sprinting: bool,
+    pub flying: bool,
+    pub game_mode: GameMode,
+    pub health: f32,
+    pub fall_distance: f32,
+    pub inventory: Inventory,
+    pub menu: Option<Menu>, // the container the player has open
+    pub experience: u32,
+    jump_held: bool,
+    double_tap_ticks: u32, // ticks left to press jump again
+}
+
+impl Player {
+    pub fn new() -> Self {
+        return Self {
+            pos: Vec3::new(0.0, 0.5, 0.0),
+            prev_pos: Vec3::new(0.0, 0.5, 0.0),
+            velocity: Vec3::new(0.0, 0.0, 0.0),
+            spawn: Vec3::new(0.0, 0.5, 0.0),
+            width: 0.6,
+            height: 1.8,
+            eye_height: 1.62,
+            step_height: 0.5,
+            collision: Collision::default(),
+            sneaking: false,
+            sprinting: false,
+            flying: false,
+            game_mode: GameMode::Creative,
+            health: MAX_HEALTH,
+            fall_distance: 0.0,
+            inventory: Inventory::new(PLAYER_INVENTORY_SIZE),
+            menu: None,
+            experience: 0,
+            jump_held: false,
+            double_tap_ticks: 0,
+        }
+    }
+    pub fn aabb(&self) -> Aabb {
+        return Aabb::from_feet(self.pos, self.width, self.height);
+    }
+    pub fn eye_pos(&self) -> Vec3 {
+        let drop = if self.sneaking { SNEAK_EYE_DROP } else { 0.0 };
+        return self.pos + Vec3::new(0.0, self.eye_height - drop, 0.0);
+    }
+    // the eye position part way from last tick to this one
+    pub fn eye_pos_at(&self, alpha: f32) -> Vec3 {
+        return self.eye_pos() + (self.prev_pos - self.pos) * (1.0 - alpha);
+    }
+    pub fn held_tool(&self, world: &World) -> Option<Tool> {
+        let stack = self.inventory.selected_stack()?;
+        return world.items[stack.item as usize].tool;
+    }
+    pub fn set_game_mode(&mut self, mode: GameMode) {
+        self.game_mode = mode;
+        let abilities = mode.abilities();
+        self.flying = abilities.always_flying || abilities.may_fly && self.flying;
+        self.fall_distance = 0.0;
+    }
+    pub fn damage(&mut self, amount: f32) {
+        if self.game_mode.abilities().invulnerable {
+            return;
+        }
+        self.health -= amount;
+        if self.health <= 0.0 {
+            self.respawn();
+        }
+    }
+    pub fn respawn(&mut self) {
+        self.pos = self.spawn;
+        self.prev_pos = self.spawn;
+        self.velocity = Vec3::new(0.0, 0.0, 0.0);
+        self.fall_distance = 0.0;
+        self.health = MAX_HEALTH;
+    }
+    // moves the player by one tick, yaw is the direction the camera is facing
+    pub fn tick(&mut self, world: &World, input: MoveInput, yaw: f32) {
+        self.prev_pos = self.pos;
+        self.update_flying(input);
+        // sprinting only works forwards and stops when running into something
+        if input.sprint && input.forward > 0.0 && !input.sneak {
+            self.sprinting = true;
+        } else if input.forward <= 0.0 || input.sneak || self.collision.hit_wall() {
+            self.sprinting = false;
+        }
+        if self.flying {
+            self.fly(world, input, yaw);
+        } else {
+            self.walk(world, input, yaw);
+        }
+        if self.pos.y < VOID_HEIGHT {
+            self.respawn();
+        }
+    }
+    // double tapping jump starts and stops flying
+    fn update_flying(&mut self, input: MoveInput) {
+        let abilities = self.game_mode.abilities();
+        self.double_tap_ticks = self.double_tap_ticks.saturating_sub(1);
+        if input.jump && !self.jump_held && abilities.may_fly && !abilities.always_flying {
+            if self.double_tap_ticks > 0 {
+                self.flying = !self.flying;
+                self.velocity.y = 0.0;
+                self.double_tap_ticks = 0;
+            } else {
+                self.double_tap_ticks = DOUBLE_TAP_TICKS;
+            }
+        }
+        self.jump_held = input.jump;
+    }
+    fn walk(&mut self, world: &World, input: MoveInput, yaw: f32) {
+        let on_ground = self.collision.on_ground;
+        self.sneaking = input.sneak;
+
+        if input.jump && on_ground {
+            self.velocity.y = JUMP_VELOCITY;
+            if self.sprinting {
+                let mut boost = Vec3::new(0.0, 0.0, SPRINT_JUMP_BOOST);
+                rotate(&mut boost, yaw);
+                self.velocity += boost;
+            }
+        }
+
+        let mut acceleration = if on_ground { GROUND_ACCELERATION } else { AIR_ACCELERATION };
+        if self.sprinting {
+            acceleration *= SPRINT_MULTIPLIER;
+        }
+        if self.sneaking {
+            acceleration *= SNEAK_MULTIPLIER;
+        }
+        self.accelerate(input, yaw, acceleration);
+
+        let mut motion = self.velocity;
+        if self.sneaking && on_ground {
+            self.clamp_to_edge(world, &mut motion);
+        }
+        let step = if on_ground { self.step_height } else { 0.0 };
+        self.move_by(world, motion, step);
+
+        // falls are only counted until landing, then they hurt
+        if self.collision.on_ground {
+            if self.fall_distance > SAFE_FALL_DISTANCE {
+                self.damage((self.fall_distance - SAFE_FALL_DISTANCE).ceil());
+            }
+            self.fall_distance = 0.0;
+        } else if self.pos.y < self.prev_pos.y {
+            self.fall_distance += self.prev_pos.y - self.pos.y;
+        }
+
+        self.velocity.y = ((self.velocity.y - GRAVITY) * AIR_DRAG).max(-TERMINAL_VELOCITY);
+        let friction = if self.collision.on_ground { GROUND_FRICTION } else { AIR_FRICTION };
+        self.velocity.x *= friction;
+        self.velocity.z *= friction;
+    }
+    fn fly(&mut self, world: &World, input: MoveInput, yaw: f32) {
+        let abilities = self.game_mode.abilities();
+        self.sneaking = false;
+        self.fall_distance = 0.0;
+
+        let mut acceleration = FLY_ACCELERATION;
+        if self.sprinting {
+            acceleration *= 2.0;
+        }
+        self.accelerate(input, yaw, acceleration);
+        if input.jump {
+            self.velocity.y += FLY_VERTICAL_SPEED;
+        }
+        if input.sneak {
+            self.velocity.y -= FLY_VERTICAL_SPEED;
+        }
+
+        if abilities.no_clip {
+            self.pos += self.velocity;
+            self.collision = Collision::default();
+        } else {
+            self.move_by(world, self.velocity, 0.0);
+            // landing stops flying
+            if self.collision.on_ground && !abilities.always_flying {
+                self.flying = false;
+            }
+        }
+
+        self.velocity.x *= AIR_FRICTION;
+        self.velocity.z *= AIR_FRICTION;
+        self.velocity.y *= FLY_VERTICAL_FRICTION;
+    }
+    // adds the input's direction turned by yaw to the velocity
+    fn accelerate(&mut self, input: MoveInput, yaw: f32, acceleration: f32) {
+        let mut wish = Vec3::new(input.strafe, 0.0, input.forward);
+        if wish.norm() > 1.0 {
+            wish = wish.normalize();
+        }
+        wish *= acceleration;
+        rotate(&mut wish, yaw);
+        self.velocity += wish;
+    }
+    // moves by motion up to the blocks in the way, stopping the velocity towards them
+    fn move_by(&mut self, world: &World, motion: Vec3, step: f32) {
+        let (moved, collision) = move_and_collide(world, self.aabb(), motion, step);
+        self.pos += moved;
+        self.collision = collision;
+        self.collision.on_ground |= is_on_ground(world, self.aabb());
+
+        if collision.hit_x || motion.x != self.velocity.x {
+            self.velocity.x = 0.0;
+        }
+        if collision.hit_z || motion.z != self.velocity.z {
+            self.velocity.z = 0.0;
+        }
+        if collision.on_ground || collision.hit_ceiling {
+            self.velocity.y = 0.0;
+        }
+    }
+    // shortens the motion so sneaking never walks off a drop taller than a step
+    fn clamp_to_edge(&self, world: &World, motion: &mut Vec3) {
+        let aabb = self.aabb();
+        let has_floor = |x: f32, z: f32| {
+            let below = aabb.offset(Vec3::new(x, -self.step_height, z));
+            return !world.collision_boxes(&below).is_empty();
+        };
+        while motion.x != 0.0 && !has_floor(motion.x, 0.0) {
+            motion.x = towards_zero(motion.x, SNEAK_EDGE_STEP);
+        }
+        while motion.z != 0.0 && !has_floor(0.0, motion.z) {
+            motion.z = towards_zero(motion.z, SNEAK_EDGE_STEP);
+        }
+        while motion.x != 0.0 && motion.z != 0.0 && !has_floor(motion.x, motion.z) {
+            motion.x = towards_zero(motion.x, SNEAK_EDGE_STEP);
+            motion.z = towards_zero(motion.z, SNEAK_EDGE_STEP);
+        }
+    }
+}
+
+fn towards_zero(value: f32, step: f32) -> f32 {
+    if value.abs() < step {
+        return 0.0;
+    }
+    return value - step * value.signum();
+}
+
+fn rotate(vec: &mut Vec3, yaw: f32) {
+    let mut view = glm::identity();
+
+    view = glm::rotate_y(&view, yaw);
+
+    let new_vec = view * glm::vec3_to_vec4(vec);
+    *vec = new_vec.xyz();
+}
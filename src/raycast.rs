@@ -0,0 +1,183 @@
+use crate::graphics::*;
+use crate::physics::Aabb;
+use crate::world::*;
+
+pub struct RayHit {
+    pub pos: BlockPos,
+    pub face: Side, // side of the block the ray went into
+    pub point: Vec3, // where it went into the block
+    pub distance: f32,
+}
+
+// walks the blocks the ray passes through in order and returns the first one whose
+// raycast shape it hits within max_distance, dir has to be a unit vector
+pub fn raycast(world: &World, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<RayHit> {
+    // blocks are centered on their position, so the grid lines are half a block off
+    let start = origin.add(Vec3::new(0.5, 0.5, 0.5));
+    let mut cell = [start.x.floor() as i32, start.y.floor() as i32, start.z.floor() as i32];
+    let mut step = [0; 3];
+    let mut next = [f32::INFINITY; 3]; // distance along the ray to the next grid line on each axis
+    let mut delta = [f32::INFINITY; 3]; // distance along the ray between grid lines on each axis
+    for axis in 0..3 {
+        if dir[axis] > 0.0 {
+            step[axis] = 1;
+            next[axis] = (cell[axis] as f32 + 1.0 - start[axis]) / dir[axis];
+            delta[axis] = 1.0 / dir[axis];
+        } else if dir[axis] < 0.0 {
+            step[axis] = -1;
+            next[axis] = (cell[axis] as f32 - start[axis]) / dir[axis];
+            delta[axis] = -1.0 / dir[axis];
+        }
+    }
+
+    let mut distance = 0.0;
+    while distance <= max_distance {
+        let pos = BlockPos::new(cell[0], cell[1], cell[2]);
+        if let Some(hit) = hit_block(world, pos, origin, dir, max_distance) {
+            return Some(hit);
+        }
+        let axis = if next[0] < next[1] && next[0] < next[2] {
+            0
+        } else if next[1] < next[2] {
+            1
+        } else {
+            2
+        };
+        distance = next[axis];
+        next[axis] += delta[axis];
+        cell[axis] += step[axis];
+    }
+    return None;
+}
+
+fn hit_block(world: &World, pos: BlockPos, origin: Vec3, dir: Vec3, max_distance: f32) -> Option<RayHit> {
+    let block = world.block_at(pos.to_vec())?;
+    let shape = block.collision_data.raycast_shape();
+    if shape.is_empty() {
+        return None;
+    }
+    let mut closest: Option<RayHit> = None;
+    for aabb in shape.boxes_at(pos.to_vec()) {
+        if let Some((distance, face)) = hit_box(&aabb, origin, dir) {
+            if distance <= max_distance && closest.as_ref().is_none_or(|hit| distance < hit.distance) {
+                closest = Some(RayHit {
+                    pos,
+                    face,
+                    point: origin + dir * distance,
+                    distance,
+                });
+            }
+        }
+    }
+    return closest;
+}
+
+// distance along the ray to where it goes into the box and the side it goes in through,
+// rays starting inside the box don't hit it
+fn hit_box(aabb: &Aabb, origin: Vec3, dir: Vec3) -> Option<(f32, Side)> {
+    let mut enter = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut face = Side::Top;
+    for axis in 0..3 {
+        if dir[axis] == 0.0 {
+            if origin[axis] < aabb.min[axis] || origin[axis] > aabb.max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let t1 = (aabb.min[axis] - origin[axis]) / dir[axis];
+        let t2 = (aabb.max[axis] - origin[axis]) / dir[axis];
+        let (near, far) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+        if near > enter {
+            enter = near;
+            face = side_of(axis, dir[axis] < 0.0);
+        }
+        exit = exit.min(far);
+    }
+    if enter > exit || enter < 0.0 {
+        return None;
+    }
+    return Some((enter, face));
+}
+
+fn side_of(axis: usize, positive: bool) -> Side {
+    match (axis, positive) {
+        (0, true) => Side::Right,
+        (0, false) => Side::Left,
+        (1, true) => Side::Top,
+        (1, false) => Side::Bottom,
+        (2, true) => Side::Back,
+        _ => Side::Front,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    // stone at 5, 5, 5 and a small button you can click through on 5, 5, 1
+    fn world() -> World {
+        let mut world = testing::world(&["stone", "button"], &[]);
+        world.blocks[2].collision_data = CollisionData::Interact(0.5, 0.5, 0.5);
+        world.place_block(Vec3::new(5.0, 5.0, 5.0), Block::new(1, NbtBlock::new(), &world));
+        world.place_block(Vec3::new(5.0, 5.0, 1.0), Block::new(2, NbtBlock::new(), &world));
+        return world;
+    }
+
+    #[test]
+    fn hits_the_face_it_comes_from() {
+        let world = world();
+        let center = Vec3::new(5.0, 5.0, 5.0);
+        for side in Side::ALL {
+            // from 3 blocks away, the face is half a block closer
+            let hit = raycast(&world, center + side.normal() * 3.0, -side.normal(), 5.0).unwrap();
+            assert_eq!(hit.pos, BlockPos::new(5, 5, 5));
+            assert_eq!(hit.face, side);
+            assert!((hit.distance - 2.5).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn hit_points_are_on_the_face() {
+        let world = world();
+        let hit = raycast(&world, Vec3::new(5.2, 5.1, 9.0), Vec3::new(0.0, 0.0, -1.0), 5.0).unwrap();
+        assert!((hit.point - Vec3::new(5.2, 5.1, 5.5)).norm() < 1e-4);
+        // from a corner, going through the middle of the block into its top
+        let dir = Vec3::new(-1.0, -2.0, -1.0).normalize();
+        let hit = raycast(&world, Vec3::new(6.0, 7.0, 6.0), dir, 5.0).unwrap();
+        assert_eq!(hit.face, Side::Top);
+        assert!((hit.point - Vec3::new(5.25, 5.5, 5.25)).norm() < 1e-4);
+    }
+
+    #[test]
+    fn misses_beyond_reach() {
+        let world = world();
+        let (origin, dir) = (Vec3::new(5.0, 5.0, 9.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(raycast(&world, origin, dir, 3.4).is_none());
+        assert!(raycast(&world, origin, dir, 3.6).is_some());
+    }
+
+    #[test]
+    fn small_boxes_are_only_hit_where_they_are() {
+        let world = world();
+        let dir = Vec3::new(0.0, 0.0, 1.0);
+        // the button is 0.5 wide, sits on the bottom of its block and goes from 0.75 to 1.25 on z
+        let hit = raycast(&world, Vec3::new(5.0, 4.7, -1.0), dir, 10.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(5, 5, 1), Side::Front));
+        assert!((hit.distance - 1.75).abs() < 1e-4);
+        // above it the ray goes on to the stone
+        let hit = raycast(&world, Vec3::new(5.0, 5.2, -1.0), dir, 10.0).unwrap();
+        assert_eq!(hit.pos, BlockPos::new(5, 5, 5));
+        assert!((hit.distance - 5.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rays_starting_inside_a_block_skip_it() {
+        let mut world = world();
+        world.place_block(Vec3::new(7.0, 5.0, 5.0), Block::new(1, NbtBlock::new(), &world));
+        let hit = raycast(&world, Vec3::new(5.0, 5.0, 5.0), Vec3::new(1.0, 0.0, 0.0), 5.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(7, 5, 5), Side::Left));
+        assert!((hit.distance - 1.5).abs() < 1e-4);
+    }
+}
@@ -1,11 +1,42 @@
 #version 330 core
 in vec2 TexCoord;
+in float AO;
+in float SkyLight;
+in vec3 BlockLight; // rgb
+in float ViewDistance;
 
 out vec4 FragColor;
 
 uniform sampler2D texture1;
+uniform float u_AlphaCutoff;
+uniform float u_SkyBrightness; // changes with the time of day
+uniform int u_FogMode; // 0 none, 1 linear, 2 exponential
+uniform vec3 u_FogColor;
+uniform float u_FogStart;
+uniform float u_FogEnd;
+uniform float u_FogDensity;
 
-void main() {
-    FragColor = texture(texture1, TexCoord);
+// how much of the fog color covers something this far from the camera
+float fogAmount(float distance) {
+    if (u_FogMode == 1) {
+        return clamp((distance - u_FogStart) / (u_FogEnd - u_FogStart), 0.0, 1.0);
+    }
+    if (u_FogMode == 2) {
+        return 1.0 - exp(-u_FogDensity * distance);
+    }
+    return 0.0;
 }
 
+void main() {
+    vec4 color = texture(texture1, TexCoord);
+    // cutout blocks like leaves and glass only keep the texels above the cutoff
+    if (color.a < u_AlphaCutoff) {
+        discard;
+    }
+    // every color channel takes whichever of the sky and block light is brighter
+    vec3 light = max(vec3(SkyLight * u_SkyBrightness), BlockLight);
+    // light levels fall off faster near the dark end, like minecraft's brightness curve
+    vec3 brightness = mix(vec3(0.05), vec3(1.0), light / (4.0 - 3.0 * light));
+    vec3 lit = color.rgb * AO * brightness;
+    FragColor = vec4(mix(lit, u_FogColor, fogAmount(ViewDistance)), color.a);
+}
@@ -0,0 +1,26 @@
+#version 330 core
+in vec4 Color;
+in float ViewDistance;
+
+out vec4 FragColor;
+
+uniform int u_FogMode; // 0 none, 1 linear, 2 exponential
+uniform vec3 u_FogColor;
+uniform float u_FogStart;
+uniform float u_FogEnd;
+uniform float u_FogDensity;
+
+// how much of the fog color covers something this far from the camera
+float fogAmount(float distance) {
+    if (u_FogMode == 1) {
+        return clamp((distance - u_FogStart) / (u_FogEnd - u_FogStart), 0.0, 1.0);
+    }
+    if (u_FogMode == 2) {
+        return 1.0 - exp(-u_FogDensity * distance);
+    }
+    return 0.0;
+}
+
+void main() {
+    FragColor = vec4(mix(Color.rgb, u_FogColor, fogAmount(ViewDistance)), Color.a);
+}
@@ -0,0 +1,16 @@
+#version 330 core
+layout(location = 0) in vec3 aPos;
+layout(location = 1) in vec4 aColor;
+
+out vec4 Color;
+out float ViewDistance;
+
+uniform mat4 u_ProjectionMatrix;
+uniform mat4 u_CameraMatrix;
+
+void main() {
+    vec4 viewPos = u_CameraMatrix * vec4(aPos, 1.0);
+    gl_Position = u_ProjectionMatrix * viewPos;
+    ViewDistance = length(viewPos.xyz);
+    Color = aColor;
+}
@@ -1,14 +1,26 @@
 #version 330 core
 layout(location = 0) in vec3 aPos;
 layout(location = 1) in vec2 aTexCoord;
+layout(location = 2) in float aAO;
+layout(location = 3) in float aSkyLight;
+layout(location = 4) in vec3 aBlockLight;
 
 out vec2 TexCoord;
+out float AO;
+out float SkyLight;
+out vec3 BlockLight;
+
+out float ViewDistance;
 
 uniform mat4 u_ProjectionMatrix;   // Projection matrix uniform
 uniform mat4 u_CameraMatrix;
 
 void main() {
-    gl_Position = u_ProjectionMatrix * u_CameraMatrix * vec4(aPos, 1.0);
+    vec4 viewPos = u_CameraMatrix * vec4(aPos, 1.0);
+    gl_Position = u_ProjectionMatrix * viewPos;
+    ViewDistance = length(viewPos.xyz);
     TexCoord = aTexCoord;
+    AO = aAO;
+    SkyLight = aSkyLight;
+    BlockLight = aBlockLight;
 }
-
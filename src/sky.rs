@@ -0,0 +1,184 @@
+use std::f32::consts::PI;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::graphics::{*, draw::*};
+use crate::world::World;
+
+const SKY_RADIUS: f32 = 50.0;
+const SUN_SIZE: f32 = 5.0;
+const MOON_SIZE: f32 = 3.5;
+const STAR_COUNT: usize = 1500;
+
+const CLOUD_HEIGHT: f32 = 64.0;
+const CLOUD_THICKNESS: f32 = 4.0;
+const CLOUD_CELL: f32 = 12.0; // blocks per cloud texel
+const CLOUD_TEXTURE_SIZE: usize = 64;
+const CLOUD_SPEED: f32 = 0.03; // blocks per tick
+const CLOUD_RANGE: i32 = 8; // cloud texels drawn around the camera
+
+pub struct Sky {
+    stars: Vec<(Vec3, f32)>, // direction and size
+    clouds: Vec<Vec<bool>>,
+}
+
+impl Sky {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut stars = Vec::new();
+        while stars.len() < STAR_COUNT {
+            let dir = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+            // only keeps points inside the sphere so the stars aren't bunched up in the corners
+            let len = dir.norm();
+            if len > 0.01 && len <= 1.0 {
+                stars.push((dir / len, rng.gen_range(0.1..0.25)));
+            }
+        }
+        return Self {
+            stars,
+            clouds: cloud_texture(&mut rng),
+        }
+    }
+    // returns the sky vertices, drawn around the camera, and the cloud vertices, drawn in the world
+    pub fn build(&self, world: &World, camera: &Camera) -> (Vec<f32>, Vec<f32>) {
+        let mut sky = Vec::new();
+        let mut clouds = Vec::new();
+        let angle = world.time_of_day() * 2.0 * PI;
+        self.build_dome(&mut sky, world);
+        self.build_stars(&mut sky, world, angle);
+        // the sun rises in +x and sets in -x, the moon is always across from it
+        let sun = Vec3::new(angle.cos(), angle.sin(), 0.0);
+        billboard(&mut sky, sun, SUN_SIZE, (1.0, 0.95, 0.7, 1.0));
+        billboard(&mut sky, -sun, MOON_SIZE, (0.85, 0.85, 0.95, 1.0));
+        self.build_clouds(&mut clouds, world, camera);
+        return (sky, clouds);
+    }
+    fn build_dome(&self, vec: &mut Vec<f32>, world: &World) {
+        let zenith = world.sky_color();
+        let horizon = world.fog_color();
+        let rings = 8;
+        let segments = 16;
+        for ring in 0..rings {
+            let pitch1 = -PI / 2.0 + PI * ring as f32 / rings as f32;
+            let pitch2 = -PI / 2.0 + PI * (ring + 1) as f32 / rings as f32;
+            let color1 = dome_color(zenith, horizon, pitch1);
+            let color2 = dome_color(zenith, horizon, pitch2);
+            for segment in 0..segments {
+                let yaw1 = 2.0 * PI * segment as f32 / segments as f32;
+                let yaw2 = 2.0 * PI * (segment + 1) as f32 / segments as f32;
+                let points = [
+                    (dome_point(pitch1, yaw1), color1),
+                    (dome_point(pitch1, yaw2), color1),
+                    (dome_point(pitch2, yaw2), color2),
+                    (dome_point(pitch1, yaw1), color1),
+                    (dome_point(pitch2, yaw2), color2),
+                    (dome_point(pitch2, yaw1), color2),
+                ];
+                for (p, c) in points {
+                    vec.extend_from_slice(&[p.x, p.y, -p.z, c.x, c.y, c.z, 1.0]);
+                }
+            }
+        }
+    }
+    fn build_stars(&self, vec: &mut Vec<f32>, world: &World, angle: f32) {
+        // fades the stars in as the sky gets dark
+        let alpha = (1.0 - (world.sky_brightness() - 0.2) / 0.8).powi(2);
+        if alpha < 0.01 {
+            return;
+        }
+        // the stars turn with the sun around the z axis
+        let (sin, cos) = (angle - PI / 2.0).sin_cos();
+        for (dir, size) in self.stars.iter() {
+            let turned = Vec3::new(dir.x * cos - dir.y * sin, dir.x * sin + dir.y * cos, dir.z);
+            billboard(vec, turned, *size, (1.0, 1.0, 1.0, alpha));
+        }
+    }
+    fn build_clouds(&self, vec: &mut Vec<f32>, world: &World, camera: &Camera) {
+        let brightness = world.sky_brightness();
+        let scroll = (world.time as f32 * CLOUD_SPEED) % (CLOUD_CELL * CLOUD_TEXTURE_SIZE as f32);
+        let center_x = ((camera.pos.x + scroll) / CLOUD_CELL).floor() as i32;
+        let center_z = (camera.pos.z / CLOUD_CELL).floor() as i32;
+        let top = (brightness, brightness, brightness, 0.8);
+        let side = (brightness * 0.9, brightness * 0.9, brightness * 0.9, 0.8);
+        let bottom = (brightness * 0.7, brightness * 0.7, brightness * 0.75, 0.8);
+        for cx in center_x - CLOUD_RANGE..=center_x + CLOUD_RANGE {
+            for cz in center_z - CLOUD_RANGE..=center_z + CLOUD_RANGE {
+                if !self.cloud_at(cx, cz) {
+                    continue;
+                }
+                let x1 = cx as f32 * CLOUD_CELL - scroll;
+                let x2 = x1 + CLOUD_CELL;
+                let z1 = cz as f32 * CLOUD_CELL;
+                let z2 = z1 + CLOUD_CELL;
+                let y1 = CLOUD_HEIGHT;
+                let y2 = CLOUD_HEIGHT + CLOUD_THICKNESS;
+                colored_square(vec, Vec3::new(x1, y2, z1), Vec3::new(x2, y2, z1), Vec3::new(x2, y2, z2), Vec3::new(x1, y2, z2), top);
+                colored_square(vec, Vec3::new(x1, y1, z1), Vec3::new(x2, y1, z1), Vec3::new(x2, y1, z2), Vec3::new(x1, y1, z2), bottom);
+                // sides are only needed where the cloud ends
+                if !self.cloud_at(cx - 1, cz) {
+                    colored_square(vec, Vec3::new(x1, y1, z1), Vec3::new(x1, y1, z2), Vec3::new(x1, y2, z2), Vec3::new(x1, y2, z1), side);
+                }
+                if !self.cloud_at(cx + 1, cz) {
+                    colored_square(vec, Vec3::new(x2, y1, z1), Vec3::new(x2, y1, z2), Vec3::new(x2, y2, z2), Vec3::new(x2, y2, z1), side);
+                }
+                if !self.cloud_at(cx, cz - 1) {
+                    colored_square(vec, Vec3::new(x1, y1, z1), Vec3::new(x2, y1, z1), Vec3::new(x2, y2, z1), Vec3::new(x1, y2, z1), side);
+                }
+                if !self.cloud_at(cx, cz + 1) {
+                    colored_square(vec, Vec3::new(x1, y1, z2), Vec3::new(x2, y1, z2), Vec3::new(x2, y2, z2), Vec3::new(x1, y2, z2), side);
+                }
+            }
+        }
+    }
+    fn cloud_at(&self, x: i32, z: i32) -> bool {
+        let size = CLOUD_TEXTURE_SIZE as i32;
+        return self.clouds[x.rem_euclid(size) as usize][z.rem_euclid(size) as usize];
+    }
+}
+
+// value noise that tiles, the cells above the threshold are clouds
+fn cloud_texture(rng: &mut StdRng) -> Vec<Vec<bool>> {
+    let lattice_size = 16;
+    let lattice: Vec<Vec<f32>> = (0..lattice_size).map(|_| (0..lattice_size).map(|_| rng.gen()).collect()).collect();
+    let scale = CLOUD_TEXTURE_SIZE as f32 / lattice_size as f32;
+    let mut texture = vec![vec![false; CLOUD_TEXTURE_SIZE]; CLOUD_TEXTURE_SIZE];
+    for (x, column) in texture.iter_mut().enumerate() {
+        for (z, cloud) in column.iter_mut().enumerate() {
+            let fx = x as f32 / scale;
+            let fz = z as f32 / scale;
+            let (x0, z0) = (fx.floor() as usize, fz.floor() as usize);
+            let (x1, z1) = ((x0 + 1) % lattice_size, (z0 + 1) % lattice_size);
+            let (tx, tz) = (smooth(fx.fract()), smooth(fz.fract()));
+            let a = lattice[x0][z0] + (lattice[x1][z0] - lattice[x0][z0]) * tx;
+            let b = lattice[x0][z1] + (lattice[x1][z1] - lattice[x0][z1]) * tx;
+            *cloud = a + (b - a) * tz > 0.6;
+        }
+    }
+    return texture;
+}
+
+fn smooth(t: f32) -> f32 {
+    return t * t * (3.0 - 2.0 * t);
+}
+
+fn dome_point(pitch: f32, yaw: f32) -> Vec3 {
+    return Vec3::new(pitch.cos() * yaw.cos(), pitch.sin(), pitch.cos() * yaw.sin()) * SKY_RADIUS;
+}
+
+// the horizon blends into the fog color and the ground below it is darker
+fn dome_color(zenith: Vec3, horizon: Vec3, pitch: f32) -> Vec3 {
+    let height = pitch.sin();
+    if height >= 0.0 {
+        return horizon.lerp(&zenith, height.sqrt());
+    }
+    return horizon.lerp(&(horizon * 0.4), (-height).sqrt());
+}
+
+// a square facing the camera from the direction dir
+fn billboard(vec: &mut Vec<f32>, dir: Vec3, size: f32, color: (f32, f32, f32, f32)) {
+    let center = dir * SKY_RADIUS * 0.9;
+    let up = if dir.y.abs() > 0.99 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+    let right = dir.cross(&up).normalize() * (size / 2.0);
+    let up = right.cross(&dir).normalize() * (size / 2.0);
+    colored_square(vec, center - right - up, center + right - up, center + right + up, center - right + up, color);
+}
@@ -0,0 +1,31 @@
+// fixtures shared by the tests
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::items::ItemData;
+use crate::world::*;
+
+static TEMP_FILES: AtomicU32 = AtomicU32::new(0);
+
+// a world with solid full blocks and plain items registered after air, in order. every
+// block registers its item too, so item ids go air, the blocks' items, then the items
+pub fn world(blocks: &[&str], items: &[&str]) -> World {
+    let mut world = World::new();
+    for name in blocks {
+        let mut data = world.blocks[0].clone();
+        data.name = name.to_string();
+        data.block_type = BlockType::Solid;
+        data.collision_data = CollisionData::Normal(1.0, 1.0, 1.0);
+        world.reg_block(data);
+    }
+    for name in items {
+        world.reg_item(ItemData::new(name, 64));
+    }
+    return world;
+}
+
+// a file in the temp directory that no other test, or another run of the tests, uses
+pub fn temp_path(name: &str) -> PathBuf {
+    let count = TEMP_FILES.fetch_add(1, Ordering::Relaxed);
+    return std::env::temp_dir().join(format!("{}_{}_{}.dat", name, std::process::id(), count));
+}
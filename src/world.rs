@@ -1,6 +1,29 @@
 use draw::Triangle;
 
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::crafting::RecipeBook;
 use crate::graphics::{*, draw::*};
+use crate::items::{ItemData, ItemStack, ToolTier, ToolType};
+use crate::loot::{LootContext, LootTable};
+use crate::nbt::Compound;
+use crate::physics::Aabb;
+use crate::player::Player;
+use crate::raycast::RayHit;
+
+mod block_entity;
+mod item_entity;
+mod lighting;
+mod save;
+mod shape;
+mod time;
+pub use block_entity::BlockEntity;
+pub use item_entity::ItemEntity;
+pub use lighting::MAX_LIGHT;
+pub use shape::VoxelShape;
+pub use time::DEFAULT_DAY_LENGTH;
 
 macro_rules! block_match {
     ($var:ident, $($name:ident => $body:block),* $(,)?) => {
@@ -10,20 +33,48 @@ macro_rules! block_match {
     };
 }
 
+macro_rules! vec3 {
+    ($x:expr, $y:expr, $z:expr) => {
+       Vec3::new($x, $y, $z) 
+    };
+}
+
+// brightness of a corner by how many of its neighbors are open
+const AO_LEVELS: [f32; 4] = [0.5, 0.7, 0.85, 1.0];
+
 pub struct World {
     chunks: Vec<Vec<[Chunk; 16]>>,
+    block_entities: HashMap<BlockPos, Box<dyn BlockEntity>>,
+    item_entities: Vec<ItemEntity>,
     pub blocks: Vec<BlockData>,
+    pub items: Vec<ItemData>,
+    pub item_tags: HashMap<String, Vec<u64>>, // by namespaced name, like minecraft:planks
+    pub recipes: RecipeBook,
+    pub loot_tables: HashMap<String, LootTable>, // by namespaced name, like minecraft:blocks/dirt_block
+    pub rng: StdRng, // for everything random that isn't seeded on its own, like loot
+    pub time: u64, // in ticks
+    pub day_length: u64, // in ticks
 }
 
 impl World {
     pub fn new() -> Self {
         let mut this = Self {
             chunks: Vec::new(),
+            block_entities: HashMap::new(),
+            item_entities: Vec::new(),
             blocks: Vec::new(),
+            items: Vec::new(),
+            item_tags: HashMap::new(),
+            recipes: RecipeBook::new(),
+            loot_tables: HashMap::new(),
+            rng: StdRng::from_entropy(),
+            time: 0,
+            day_length: DEFAULT_DAY_LENGTH,
         };
         this.reg_block(BlockData {
             model: ModelType::Block(BlockModelType {
                 block_size: (1.0, 1.0, 1.0),
+                offset: (0.0, 0.0, 0.0),
                 texture: BlockTextureType::None,
             }),
             rotate: false,
@@ -34,14 +85,59 @@ impl World {
             update: None,
             start: None,
             block_type: BlockType::None,
+            light_emission: (0, 0, 0),
+            light_opacity: 0,
+            light_filter: (0, 0, 0),
+            fog: None,
+            hardness: 0.0,
+            preferred_tool: None,
+            required_tier: None,
+            on_use: None,
+            block_entity: None,
+            loot_table: None,
         });
         this.chunks.push(vec![core::array::from_fn(|_| Chunk::new(&this))]);
         return this;
     }
+    pub fn tick(&mut self) {
+        self.time = self.time.wrapping_add(1);
+        self.tick_block_entities();
+        self.tick_item_entities();
+    }
+    // every block also gets an item that places it
     pub fn reg_block(&mut self, data: BlockData) {
+        self.reg_item(ItemData::block_item(&data.name, self.blocks.len() as u64));
         self.blocks.push(data)
     }
+    pub fn reg_item(&mut self, data: ItemData) {
+        self.items.push(data)
+    }
+    pub fn find_block(&self, name: &str) -> Option<u64> {
+        return self.blocks.iter().position(|block| block.name == name).map(|x| x as u64);
+    }
+    pub fn block_item(&self, block: u64) -> Option<u64> {
+        return self.items.iter().position(|item| item.block == Some(block)).map(|x| x as u64);
+    }
+    // by the id data files use, the namespace can be left out
+    pub fn find_item(&self, id: &str) -> Option<u64> {
+        let id = crate::crafting::namespaced(id);
+        return self.items.iter().position(|item| item.id() == id).map(|x| x as u64);
+    }
+    pub fn item_stack(&self, item: u64, count: u32) -> ItemStack {
+        return ItemStack::new(item, count, self.items[item as usize].max_stack);
+    }
+    // rolls a loot table with the world's rng, nothing for tables that don't exist
+    pub fn roll_loot(&mut self, name: &str, context: &LootContext) -> Vec<ItemStack> {
+        let Some(table) = self.loot_tables.get(name) else {
+            eprintln!("unknown loot table {}", name);
+            return Vec::new();
+        };
+        return table.roll(context, &self.items, &mut self.rng);
+    }
     pub fn place_block(&mut self, vec: Vec3, block: Block) {
+        if vec.x < 0.0 || vec.y < 0.0 || vec.z < 0.0 {
+            return;
+        }
         let (chunk, offset) = self.get_chunk(vec);
         if let Some(x) = self.chunks.get_mut(chunk.x as usize) {
             if let Some(z) = x.get_mut(chunk.z as usize) {
@@ -50,6 +146,8 @@ impl World {
                         y.add_block(offset, &b.block_type); 
                     }
                     y.blocks[offset.x as usize][offset.y as usize][offset.z as usize] = block;
+                    self.update_light(vec);
+                    self.replace_block_entity(BlockPos::from_vec(vec), true);
                 }
             }
         } 
@@ -77,111 +175,435 @@ impl World {
         } 
         return None;
     }
-    pub fn render<T>(&self, vert: &mut Vec<f32>, player: Vec3, window: &Window<T>) {
-        let (chunk, _) = self.get_chunk(player); 
-        if let Some(x) = self.chunks.get(chunk.x as usize) {
-            if let Some(z) = x.get(chunk.z as usize) {
-                if let Some(y) = z.get(chunk.y as usize) {
-                    let blocks = y.get_mesh_data(true);
-                    for block in blocks.iter() {
-                       let pos = chunk.mul(Vec3::new(16.0, 16.0, 16.0)).add(block.pos); 
-                       block_match! {
-                           block,
-                           top => {
-                               self.render_side(vert, Side::Top, pos, block.model_data.clone(), window);
-                           },
-                           bottom => {
-                               self.render_side(vert, Side::Bottom, pos, block.model_data.clone(), window);
-                           },
-                           left => {
-                               self.render_side(vert, Side::Left, pos, block.model_data.clone(), window);
-                           },
-                           right => {
-                               self.render_side(vert, Side::Right, pos, block.model_data.clone(), window);
-                           },
-                           front => {
-                               self.render_side(vert, Side::Front, pos, block.model_data.clone(), window);
-                           },
-                           back => {
-                               self.render_side(vert, Side::Back, pos, block.model_data.clone(), window);
-                           },
-                       }
+    fn chunk_at(&self, vec: Vec3) -> Option<(&Chunk, Vec3)> {
+        if vec.x < 0.0 || vec.y < 0.0 || vec.z < 0.0 {
+            return None;
+        }
+        let (chunk, offset) = self.get_chunk(vec);
+        let chunk = self.chunks.get(chunk.x as usize)?.get(chunk.z as usize)?.get(chunk.y as usize)?;
+        return Some((chunk, offset));
+    }
+    fn chunk_at_mut(&mut self, vec: Vec3) -> Option<(&mut Chunk, Vec3)> {
+        if vec.x < 0.0 || vec.y < 0.0 || vec.z < 0.0 {
+            return None;
+        }
+        let (chunk, offset) = self.get_chunk(vec);
+        let chunk = self.chunks.get_mut(chunk.x as usize)?.get_mut(chunk.z as usize)?.get_mut(chunk.y as usize)?;
+        return Some((chunk, offset));
+    }
+    pub fn block_at(&self, vec: Vec3) -> Option<&Block> {
+        let (chunk, offset) = self.chunk_at(vec)?;
+        return chunk.blocks.get(offset.x as usize)?
+            .get(offset.y as usize)?
+            .get(offset.z as usize);
+    }
+    pub fn is_solid(&self, vec: Vec3) -> bool {
+        if let Some((chunk, offset)) = self.chunk_at(vec) {
+            return chunk.solid[offset.x as usize][offset.y as usize][offset.z as usize];
+        }
+        return false;
+    }
+    // runs the on_use of the block at pos, the block is given as a copy that is put back
+    // afterwards unless the callback replaced it
+    pub fn use_block(&mut self, pos: Vec3, player: &mut Player, hit: &RayHit) -> bool {
+        let Some(mut block) = self.block_at(pos).cloned() else {
+            return false;
+        };
+        let Some(on_use) = self.blocks[block.id as usize].on_use else {
+            return false;
+        };
+        let used = on_use(pos, &mut block, self, &mut BlockUse {
+            player,
+            hit,
+        });
+        if let Some(current) = self.get_block(pos) {
+            if current.id == block.id {
+                *current = block;
+            }
+        }
+        return used;
+    }
+    // boxes of every block touching area that entities can't move through
+    pub fn collision_boxes(&self, area: &Aabb) -> Vec<Aabb> {
+        let mut boxes = Vec::new();
+        // blocks are centered on their position so they reach half a block out
+        let min = area.min.add(vec3![0.5, 0.5, 0.5]).floor();
+        let max = area.max.add(vec3![0.5, 0.5, 0.5]).floor();
+        for x in min.x as i32..=max.x as i32 {
+            for y in min.y as i32..=max.y as i32 {
+                for z in min.z as i32..=max.z as i32 {
+                    let pos = vec3![x as f32, y as f32, z as f32];
+                    if let Some(block) = self.block_at(pos) {
+                        let shape = block.collision_data.collision_shape();
+                        if shape.is_empty() {
+                            continue;
+                        }
+                        boxes.extend(shape.boxes_at(pos).into_iter().filter(|b| b.intersects(area)));
                     }
                 }
             }
-        } 
+        }
+        return boxes;
     }
-    fn render_side<T>(&self, vert: &mut Vec<f32>, face: Side, pos: Vec3, model_data: ModelType, window: &Window<T>) {
+    // every section in columns up to distance chunks away from the camera
+    pub fn render<T>(&self, vert: &mut Vec<f32>, translucent: &mut Vec<f32>, camera: Vec3, distance: u32, window: &Window<T>) {
+        let (center, _) = self.get_chunk(camera);
+        let mut see_through = Vec::new();
+        for (chunk_x, x) in self.chunks.iter().enumerate() {
+            for (chunk_z, z) in x.iter().enumerate() {
+                if (chunk_x as f32 - center.x).abs() > distance as f32 || (chunk_z as f32 - center.z).abs() > distance as f32 {
+                    continue;
+                }
+                for (chunk_y, y) in z.iter().enumerate() {
+                    let offset = Vec3::new(chunk_x as f32, chunk_y as f32, chunk_z as f32).mul(Vec3::new(16.0, 16.0, 16.0));
+                    let mut faces = Vec::new();
+                    for block in y.get_mesh_data(true).iter_mut() {
+                        self.cull_faces(block, offset.add(block.pos));
+                        self.render_block(&mut faces, block, offset.add(block.pos), window);
+                    }
+                    for face in faces.iter() {
+                        face.to_points(vert);
+                    }
+                    for block in y.get_mesh_data(false).iter_mut() {
+                        self.cull_faces(block, offset.add(block.pos));
+                        self.render_block(&mut see_through, block, offset.add(block.pos), window);
+                    }
+                }
+            }
+        }
+        // translucent faces of every section are sorted together so they blend back to front
+        for face in sort_faces(see_through, window.camera.pos).iter() {
+            face.to_points(translucent);
+        }
+    }
+    fn render_block<T>(&self, vert: &mut Vec<Triangle>, block: &BlockFaces, pos: Vec3, window: &Window<T>) {
+        if let ModelType::Plant(plant) = &block.model_data {
+            self.render_plant(vert, pos, plant, &window.shaders.get_texture(plant.texture.get_texture_name()));
+            return;
+        }
+        block_match! {
+            block,
+            top => {
+                self.render_side(vert, Side::Top, pos, block.model_data.clone(), window);
+            },
+            bottom => {
+                self.render_side(vert, Side::Bottom, pos, block.model_data.clone(), window);
+            },
+            left => {
+                self.render_side(vert, Side::Left, pos, block.model_data.clone(), window);
+            },
+            right => {
+                self.render_side(vert, Side::Right, pos, block.model_data.clone(), window);
+            },
+            front => {
+                self.render_side(vert, Side::Front, pos, block.model_data.clone(), window);
+            },
+            back => {
+                self.render_side(vert, Side::Back, pos, block.model_data.clone(), window);
+            },
+        }
+    }
+    fn render_side<T>(&self, vert: &mut Vec<Triangle>, face: Side, pos: Vec3, model_data: ModelType, window: &Window<T>) {
         if let ModelType::Block(block) = model_data {
-            let data = generate_face_vertices(pos, &face); 
-            Triangle::create_square(vert, data[0], data[1], data[2], data[3], &window.shaders.get_texture(
-                    block.get_texture(&face)
-            ));
+            let data = generate_face_vertices(pos, &block, &face); 
+            let uvs = generate_face_uvs(pos, &data, &face);
+            let shades = data.map(|vertex| self.vertex_shade(pos, vertex, &face));
+            Triangle::square_mapped(vert, Quad { corners: data, uvs, shades }, &window.shaders.get_texture(block.get_texture(&face)));
+        }
+    }
+    // the destroy stage texture over every side of the block at pos, lit like the block
+    pub fn render_cracks(&self, vert: &mut Vec<f32>, pos: Vec3, texture: &TextureLocation) {
+        let Some(block) = self.block_at(pos) else {
+            return;
+        };
+        let model = match &block.model_data {
+            ModelType::Block(model) => model.clone(),
+            // plants are cracked like a full block
+            ModelType::Plant(_) => BlockModelType {
+                block_size: (1.0, 1.0, 1.0),
+                offset: (0.0, 0.0, 0.0),
+                texture: BlockTextureType::None,
+            },
+        };
+        let mut triangles = Vec::new();
+        for face in Side::ALL.iter() {
+            // pushed out a little so the cracks don't flicker against the block
+            let data = generate_face_vertices(pos, &model, face).map(|vertex| vertex.add(face.normal().mul(vec3![0.002, 0.002, 0.002])));
+            let uvs = generate_face_uvs(pos, &data, face);
+            let shades = data.map(|vertex| self.vertex_shade(pos, vertex, face));
+            Triangle::square_mapped(&mut triangles, Quad { corners: data, uvs, shades }, texture);
+        }
+        for triangle in triangles.iter() {
+            triangle.to_points(vert);
+        }
+    }
+    // darkens a corner of a face by the solid blocks around it on the side the face points to,
+    // and smooths the light over the open voxels touching that corner
+    fn vertex_shade(&self, pos: Vec3, vertex: Vec3, face: &Side) -> Shade {
+        let front = pos.add(face.normal());
+        let mut sides = [vec3![0.0, 0.0, 0.0], vec3![0.0, 0.0, 0.0]];
+        let mut i = 0;
+        for axis in 0..3 {
+            if axis == face.axis() {
+                continue;
+            }
+            sides[i][axis] = if vertex[axis] < pos[axis] { -1.0 } else { 1.0 };
+            i += 1;
+        }
+        let side1 = self.is_solid(front.add(sides[0]));
+        let side2 = self.is_solid(front.add(sides[1]));
+        let corner = self.is_solid(front.add(sides[0]).add(sides[1]));
+        let level = if side1 && side2 {
+            0
+        } else {
+            3 - (side1 as usize + side2 as usize + corner as usize)
+        };
+
+        let mut samples = vec![front];
+        if !side1 {
+            samples.push(front.add(sides[0]));
+        }
+        if !side2 {
+            samples.push(front.add(sides[1]));
+        }
+        if !(corner || side1 && side2) {
+            samples.push(front.add(sides[0]).add(sides[1]));
+        }
+        let mut shade = Shade::new();
+        shade.ao = AO_LEVELS[level];
+        shade.sky = samples.iter().map(|s| self.get_sky_light(*s) as f32).sum::<f32>() / (samples.len() as f32 * MAX_LIGHT as f32);
+        for sample in samples.iter() {
+            let (r, g, b) = self.get_block_light(*sample);
+            shade.block.0 += r as f32;
+            shade.block.1 += g as f32;
+            shade.block.2 += b as f32;
+        }
+        let total = samples.len() as f32 * MAX_LIGHT as f32;
+        shade.block = (shade.block.0 / total, shade.block.1 / total, shade.block.2 / total);
+        return shade;
+    }
+    fn cull_faces(&self, block: &mut BlockFaces, pos: Vec3) {
+        if let ModelType::Block(model) = &block.model_data {
+            block.top = !self.face_covered(pos, Side::Top, model);
+            block.bottom = !self.face_covered(pos, Side::Bottom, model);
+            block.left = !self.face_covered(pos, Side::Left, model);
+            block.right = !self.face_covered(pos, Side::Right, model);
+            block.front = !self.face_covered(pos, Side::Front, model);
+            block.back = !self.face_covered(pos, Side::Back, model);
+        }
+    }
+    // a face is only hidden when the opaque neighbor's touching faces cover all of it,
+    // translucent blocks like glass are also hidden by more of the same block
+    fn face_covered(&self, pos: Vec3, face: Side, model: &BlockModelType) -> bool {
+        let neighbor = match self.block_at(pos.add(face.normal())) {
+            Some(b) => b,
+            None => return false,
+        };
+        let same = self.block_at(pos).is_some_and(|x| x.translucent && x.id == neighbor.id);
+        if !neighbor.solid && !same {
+            return false;
+        }
+        if let ModelType::Block(other) = &neighbor.model_data {
+            // only the parts inside each block can meet at the face between them
+            let full = VoxelShape::full();
+            return model.shape().intersection(&full).face_covered_by(face, &other.shape().intersection(&full));
+        }
+        return false;
+    }
+    fn render_plant(&self, vert: &mut Vec<Triangle>, pos: Vec3, plant: &PlantModelType, texture: &TextureLocation) {
+        let mut pos = pos;
+        if plant.random_offset {
+            let (x, z) = plant_offset(pos);
+            pos.x += x;
+            pos.z += z;
+        }
+        let mut shade = Shade::new();
+        shade.sky = self.get_sky_light(pos.round()) as f32 / MAX_LIGHT as f32;
+        let (r, g, b) = self.get_block_light(pos.round());
+        shade.block = (r as f32 / MAX_LIGHT as f32, g as f32 / MAX_LIGHT as f32, b as f32 / MAX_LIGHT as f32);
+        let uvs = [(0.0, 1.0), (1.0, 1.0), (1.0, 0.0), (0.0, 0.0)];
+        // the two quads run corner to corner through the block
+        let diagonals = [(-0.45, -0.45, 0.45, 0.45), (-0.45, 0.45, 0.45, -0.45)];
+        for (x1, z1, x2, z2) in diagonals {
+            let bl = Vec3::new(pos.x + x1, pos.y - 0.5, pos.z + z1);
+            let br = Vec3::new(pos.x + x2, pos.y - 0.5, pos.z + z2);
+            let tr = Vec3::new(pos.x + x2, pos.y + 0.5, pos.z + z2);
+            let tl = Vec3::new(pos.x + x1, pos.y + 0.5, pos.z + z1);
+            // both windings so the quad survives back face culling from either side
+            Triangle::square_mapped(vert, Quad { corners: [bl, br, tr, tl], uvs, shades: [shade; 4] }, texture);
+            Triangle::square_mapped(vert, Quad { corners: [br, bl, tl, tr], uvs, shades: [shade; 4] }, texture);
         }
     }
 }
 
-macro_rules! vec3 {
-    ($x:expr, $y:expr, $z:expr) => {
-       Vec3::new($x, $y, $z) 
-    };
+// same hash minecraft uses so flowers and grass don't line up on the block grid
+fn plant_offset(pos: Vec3) -> (f32, f32) {
+    let (x, y, z) = (pos.x as i64, pos.y as i64, pos.z as i64);
+    let mut seed = x.wrapping_mul(3129871) ^ z.wrapping_mul(116129781) ^ y;
+    seed = seed.wrapping_mul(seed).wrapping_mul(42317861).wrapping_add(seed.wrapping_mul(11));
+    seed >>= 16;
+    let x = ((seed & 15) as f32 / 15.0 - 0.5) * 0.5;
+    let z = ((seed >> 8 & 15) as f32 / 15.0 - 0.5) * 0.5;
+    return (x, z);
+}
+
+// takes the squares made by Triangle::square and orders them back to front from the camera
+fn sort_faces(faces: Vec<Triangle>, camera: Vec3) -> Vec<Triangle> {
+    let mut squares = Vec::new();
+    let mut iter = faces.into_iter();
+    while let (Some(a), Some(b)) = (iter.next(), iter.next()) {
+        // p1 and p3 of the first triangle are opposite corners of the square
+        let center = (a.p1 + a.p3) * 0.5;
+        squares.push(((center - camera).norm_squared(), [a, b]));
+    }
+    squares.sort_by(|a, b| b.0.total_cmp(&a.0));
+    return squares.into_iter().flat_map(|(_, square)| square).collect();
 }
 
-fn generate_face_vertices(position: Vec3, face: &Side) -> [Vec3; 4] {
+
+fn generate_face_vertices(position: Vec3, model: &BlockModelType, face: &Side) -> [Vec3; 4] {
+    let (min, max) = model.bounds();
+    let min = position.add(min).sub(vec3![0.5, 0.5, 0.5]);
+    let max = position.add(max).sub(vec3![0.5, 0.5, 0.5]);
     match face {
         Side::Top => [
             // Top face
-            vec3![position.x - 0.5, position.y + 0.5, position.z - 0.5], // Top-left
-            vec3![position.x + 0.5, position.y + 0.5, position.z - 0.5], // Top-right
-            vec3![position.x + 0.5, position.y + 0.5, position.z + 0.5], // Bottom-right
-            vec3![position.x - 0.5, position.y + 0.5, position.z + 0.5], // Bottom-left
+            vec3![min.x, max.y, min.z], // Top-left
+            vec3![max.x, max.y, min.z], // Top-right
+            vec3![max.x, max.y, max.z], // Bottom-right
+            vec3![min.x, max.y, max.z], // Bottom-left
         ],
         Side::Bottom => [
             // Bottom face
-            vec3![position.x - 0.5, position.y - 0.5, position.z - 0.5], // Bottom-left
-            vec3![position.x + 0.5, position.y - 0.5, position.z - 0.5], // Bottom-right
-            vec3![position.x + 0.5, position.y - 0.5, position.z + 0.5], // Top-right
-            vec3![position.x - 0.5, position.y - 0.5, position.z + 0.5], // Top-left
+            vec3![min.x, min.y, min.z], // Bottom-left
+            vec3![max.x, min.y, min.z], // Bottom-right
+            vec3![max.x, min.y, max.z], // Top-right
+            vec3![min.x, min.y, max.z], // Top-left
         ],
         Side::Front => [
             // Front face
-            vec3![position.x - 0.5, position.y - 0.5, position.z - 0.5], // Bottom-left
-            vec3![position.x + 0.5, position.y - 0.5, position.z - 0.5], // Bottom-right
-            vec3![position.x + 0.5, position.y + 0.5, position.z - 0.5], // Top-right
-            vec3![position.x - 0.5, position.y + 0.5, position.z - 0.5], // Top-left
+            vec3![min.x, min.y, min.z], // Bottom-left
+            vec3![max.x, min.y, min.z], // Bottom-right
+            vec3![max.x, max.y, min.z], // Top-right
+            vec3![min.x, max.y, min.z], // Top-left
         ],
         Side::Back => [
             // Back face
-            vec3![position.x - 0.5, position.y - 0.5, position.z + 0.5], // Bottom-right
-            vec3![position.x + 0.5, position.y - 0.5, position.z + 0.5], // Bottom-left
-            vec3![position.x + 0.5, position.y + 0.5, position.z + 0.5], // Top-left
-            vec3![position.x - 0.5, position.y + 0.5, position.z + 0.5], // Top-right
+            vec3![min.x, min.y, max.z], // Bottom-right
+            vec3![max.x, min.y, max.z], // Bottom-left
+            vec3![max.x, max.y, max.z], // Top-left
+            vec3![min.x, max.y, max.z], // Top-right
         ],
         Side::Left => [
             // Left face
-            vec3![position.x - 0.5, position.y - 0.5, position.z - 0.5], // Bottom-left
-            vec3![position.x - 0.5, position.y - 0.5, position.z + 0.5], // Top-left
-            vec3![position.x - 0.5, position.y + 0.5, position.z + 0.5], // Top-right
-            vec3![position.x - 0.5, position.y + 0.5, position.z - 0.5], // Bottom-right
+            vec3![min.x, min.y, min.z], // Bottom-left
+            vec3![min.x, min.y, max.z], // Top-left
+            vec3![min.x, max.y, max.z], // Top-right
+            vec3![min.x, max.y, min.z], // Bottom-right
         ],
         Side::Right => [
             // Right face
-            vec3![position.x + 0.5, position.y - 0.5, position.z - 0.5], // Bottom-right
-            vec3![position.x + 0.5, position.y - 0.5, position.z + 0.5], // Top-right
-            vec3![position.x + 0.5, position.y + 0.5, position.z + 0.5], // Top-left
-            vec3![position.x + 0.5, position.y + 0.5, position.z - 0.5], // Bottom-left
+            vec3![max.x, min.y, min.z], // Bottom-right
+            vec3![max.x, min.y, max.z], // Top-right
+            vec3![max.x, max.y, max.z], // Top-left
+            vec3![max.x, max.y, min.z], // Bottom-left
         ],
     }
 }
 
-#[derive(Clone)]
-enum Side {
-    Top,
-    Bottom,
-    Left,
-    Right,
-    Front,
-    Back,
+// crops the texture to the part of the face that the model covers,
+// a full block gets the same corners as TopLeft, TopRight, BottomRight and BottomLeft
+fn generate_face_uvs(position: Vec3, vertices: &[Vec3; 4], face: &Side) -> [(f32, f32); 4] {
+    let corner = position.sub(vec3![0.5, 0.5, 0.5]);
+    return vertices.map(|vertex| {
+        let local = vertex.sub(corner);
+        match face {
+            Side::Top | Side::Bottom => (local.x, 1.0 - local.z),
+            Side::Front | Side::Back => (local.x, 1.0 - local.y),
+            Side::Left | Side::Right => (local.z, 1.0 - local.y),
+        }
+    });
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Side {
+    Top, // +y
+    Bottom, // -y
+    Left, // -x
+    Right, // +x
+    Front, // -z
+    Back, // +z
+}
+
+impl Side {
+    pub const ALL: [Side; 6] = [Side::Top, Side::Bottom, Side::Left, Side::Right, Side::Front, Side::Back];
+
+    pub fn normal(&self) -> Vec3 {
+        match self {
+            Self::Top => vec3![0.0, 1.0, 0.0],
+            Self::Bottom => vec3![0.0, -1.0, 0.0],
+            Self::Left => vec3![-1.0, 0.0, 0.0],
+            Self::Right => vec3![1.0, 0.0, 0.0],
+            Self::Front => vec3![0.0, 0.0, -1.0],
+            Self::Back => vec3![0.0, 0.0, 1.0],
+        }
+    }
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Front => Self::Back,
+            Self::Back => Self::Front,
+        }
+    }
+    // index of the axis the side faces along
+    pub fn axis(&self) -> usize {
+        match self {
+            Self::Left | Self::Right => 0,
+            Self::Top | Self::Bottom => 1,
+            Self::Front | Self::Back => 2,
+        }
+    }
+    // whether a box from min to max (0 to 1 inside the block) touches this side of the block
+    pub fn on_edge(&self, min: Vec3, max: Vec3) -> bool {
+        match self {
+            Self::Top | Self::Right | Self::Back => max[self.axis()] >= 1.0,
+            Self::Bottom | Self::Left | Self::Front => min[self.axis()] <= 0.0,
+        }
+    }
+}
+
+// position of a block, blocks are centered on it
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        return Self {
+            x,
+            y,
+            z,
+        }
+    }
+    // the block a point is inside of
+    pub fn from_vec(vec: Vec3) -> Self {
+        let vec = vec.add(vec3![0.5, 0.5, 0.5]).floor();
+        return Self::new(vec.x as i32, vec.y as i32, vec.z as i32);
+    }
+    pub fn to_vec(self) -> Vec3 {
+        return vec3![self.x as f32, self.y as f32, self.z as f32];
+    }
+    // the block next to this one on side
+    pub fn offset(&self, side: Side) -> Self {
+        let normal = side.normal();
+        return Self::new(self.x + normal.x as i32, self.y + normal.y as i32, self.z + normal.z as i32);
+    }
 }
 
 #[derive(Clone)]
@@ -190,6 +612,8 @@ struct Chunk {
 
     transparent: [[[bool; 16]; 16]; 16],
 
+    light: [[[u16; 16]; 16]; 16], // sky, red, green and blue light, 4 bits each
+
     blocks: Vec<Vec<Vec<Block>>>,
 }
 
@@ -214,6 +638,8 @@ impl Chunk {
 
             transparent: [[[false; 16]; 16]; 16], // xyz
 
+            light: [[[(MAX_LIGHT as u16) << 12; 16]; 16]; 16], // xyz, starts out as open sky
+
             blocks: default(world),
         }
     }
@@ -229,7 +655,9 @@ impl Chunk {
 
                 self.transparent[pos.x as usize][pos.y as usize][pos.z as usize] = true;
             }
-            BlockType::Transparent => {
+            BlockType::Transparent | BlockType::Cutout => {
+                self.solid[pos.x as usize][pos.y as usize][pos.z as usize] = false;
+
                 self.transparent[pos.x as usize][pos.y as usize][pos.z as usize] = true;
             }
         }
@@ -240,7 +668,7 @@ impl Chunk {
             for y in 0..16 {
                 for z in 0..16 {
                     if solid {
-                        if self.blocks[x][y][z].solid {
+                        if self.blocks[x][y][z].transparent && !self.blocks[x][y][z].translucent {
                             vec.push(BlockFaces {
                                 pos: Vec3::new(x as f32, y as f32, z as f32),
                                 model_data: self.blocks[x][y][z].model_data.clone(),
@@ -253,7 +681,7 @@ impl Chunk {
                             });          
                         }
                     } else {
-                        if self.blocks[x][y][z].transparent {
+                        if self.blocks[x][y][z].translucent {
                             vec.push(BlockFaces {
                                 pos:Vec3::new(x as f32, y as f32, z as f32),
                                 model_data: self.blocks[x][y][z].model_data.clone(),
@@ -278,21 +706,21 @@ struct BlockFaces {
     model_data: ModelType,
     top: bool,
     bottom: bool,
-    front: bool, // +x
-    back: bool, // -x
-    left: bool, // -z
-    right: bool, // +z
+    front: bool, // -z
+    back: bool, // +z
+    left: bool, // -x
+    right: bool, // +x
 }
 
 #[derive(Clone)]
 pub struct NbtBlock {
-    
+    pub data: Compound, // block entities load from and save to this
 }
 
 impl NbtBlock {
     pub fn new() -> Self {
         return Self {
-
+            data: Compound::new(),
         };
     }
 }
@@ -343,6 +771,7 @@ pub struct Block {
     pub collision_data: CollisionData,
     solid: bool,
     transparent: bool,
+    translucent: bool,
 }
 
 impl Block {
@@ -355,6 +784,7 @@ impl Block {
             collision_data: world.blocks.get(id as usize).unwrap().collision_data.clone(),
             solid: *block_type == BlockType::Solid,
             transparent: *block_type != BlockType::None,
+            translucent: *block_type == BlockType::Transparent,
         }
     }
     pub fn get_id(&self) -> u64 {
@@ -366,7 +796,8 @@ impl Block {
 pub enum BlockType {
     None,
     Solid,
-    Transparent,
+    Transparent, // blended, drawn in the translucent pass
+    Cutout, // drawn with the opaque blocks, fully transparent pixels are discarded
 }
 
 impl Clone for Box<dyn TextureName> {
@@ -378,16 +809,33 @@ impl Clone for Box<dyn TextureName> {
 #[derive(Clone)]
 pub enum ModelType {
     Block(BlockModelType),
-    Plant(Box<dyn TextureName>),
+    Plant(PlantModelType),
+}
+
+#[derive(Clone)]
+pub struct PlantModelType {
+    pub texture: Box<dyn TextureName>,
+    pub random_offset: bool, // shifts the plant on x and z depending on its position
 }
 
 #[derive(Clone)]
 pub struct BlockModelType {
     pub block_size: (f32, f32, f32),
+    pub offset: (f32, f32, f32), // from the bottom corner of the block to the bottom corner of the model
     pub texture: BlockTextureType,
 }
 
 impl BlockModelType {
+    // corners of the model inside the block, from 0 to 1 on each axis
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let min = Vec3::new(self.offset.0, self.offset.1, self.offset.2);
+        let max = min.add(Vec3::new(self.block_size.0, self.block_size.1, self.block_size.2));
+        return (min, max);
+    }
+    pub fn shape(&self) -> VoxelShape {
+        let (min, max) = self.bounds();
+        return VoxelShape::cuboid(min, max);
+    }
     pub fn get_texture(&self, side: &Side) -> String {
         match &self.texture {
             BlockTextureType::None => {panic!("cant render None")},
@@ -413,17 +861,72 @@ impl BlockModelType {
     }
 }
 
-#[derive(Clone)]
+// a box inside a block
+#[derive(Clone, Copy, Debug)]
 pub struct BlockCollision {
-    pub offset: Vec3,
+    pub offset: Vec3, // from the bottom corner of the block, from 0 to 1 on each axis
     pub size: (f32, f32, f32),
 }
 
+impl BlockCollision {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        return Self {
+            offset: min,
+            size: (max.x - min.x, max.y - min.y, max.z - min.z),
+        }
+    }
+    pub fn min(&self) -> Vec3 {
+        return self.offset;
+    }
+    pub fn max(&self) -> Vec3 {
+        return self.offset.add(vec3![self.size.0, self.size.1, self.size.2]);
+    }
+}
+
+// shapes of a block for each thing that needs one
+#[derive(Clone)]
+pub struct BlockShapes {
+    pub collision: VoxelShape, // what entities can't move through
+    pub outline: VoxelShape, // drawn around the block when it is targeted
+    pub raycast: VoxelShape, // what can be clicked
+}
+
 #[derive(Clone)]
 pub enum CollisionData {
     None,
-    Interact(f32, f32, f32),
+    Interact(f32, f32, f32), // can be clicked but not collided with
     Normal(f32, f32, f32),
+    Shapes(BlockShapes),
+}
+
+impl CollisionData {
+    pub fn collision_shape(&self) -> VoxelShape {
+        match self {
+            Self::None | Self::Interact(..) => VoxelShape::empty(),
+            Self::Normal(x, y, z) => centered_box(*x, *y, *z),
+            Self::Shapes(shapes) => shapes.collision.clone(),
+        }
+    }
+    pub fn outline_shape(&self) -> VoxelShape {
+        match self {
+            Self::None => VoxelShape::empty(),
+            Self::Interact(x, y, z) | Self::Normal(x, y, z) => centered_box(*x, *y, *z),
+            Self::Shapes(shapes) => shapes.outline.clone(),
+        }
+    }
+    pub fn raycast_shape(&self) -> VoxelShape {
+        match self {
+            Self::None => VoxelShape::empty(),
+            Self::Interact(x, y, z) | Self::Normal(x, y, z) => centered_box(*x, *y, *z),
+            Self::Shapes(shapes) => shapes.raycast.clone(),
+        }
+    }
+}
+
+// the sizes are centered on x and z and start at the bottom of the block
+fn centered_box(x: f32, y: f32, z: f32) -> VoxelShape {
+    let min = vec3![(1.0 - x) / 2.0, 0.0, (1.0 - z) / 2.0];
+    return VoxelShape::cuboid(min, min.add(vec3![x, y, z]));
 }
 
 #[derive(Clone)]
@@ -437,5 +940,97 @@ pub struct BlockData {
     pub start: Option<fn(Vec3, &mut Block, &mut World) -> ()>,
     pub random_tick: Option<fn(Vec3, &mut Block, &mut World) -> ()>,
     pub block_type: BlockType,
+    pub light_emission: (u8, u8, u8), // rgb, 0 to 15
+    pub light_opacity: u8, // how many levels light loses going through the block, 15 blocks it
+    pub light_filter: (u8, u8, u8), // extra opacity for each block light color, tints the light going through
+    pub fog: Option<Fog>, // replaces the distance fog while the camera is inside the block, like Fog::water()
+    pub hardness: f32, // how long it takes to break, 0 breaks instantly and below 0 never breaks
+    pub preferred_tool: Option<ToolType>, // the tool that breaks it faster
+    pub required_tier: Option<ToolTier>, // it only drops when broken with the preferred tool of at least this tier
+    // right clicking the block, returns whether it did something so nothing gets placed against it
+    pub on_use: Option<UseCallback>,
+    pub block_entity: Option<fn() -> Box<dyn BlockEntity>>, // makes the block entity when the block is placed
+    pub loot_table: Option<String>, // what it drops when broken, like minecraft:blocks/dirt_block
+}
+
+// the block's position, a copy of it to change, the world and who is using it
+pub type UseCallback = fn(Vec3, &mut Block, &mut World, &mut BlockUse) -> bool;
+
+// who is using a block and where they clicked it
+pub struct BlockUse<'a> {
+    pub player: &'a mut Player,
+    pub hit: &'a RayHit,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn translucent_faces_are_culled_by_the_same_block() {
+        let mut world = testing::world(&["stone", "glass", "ice"], &[]);
+        world.blocks[2].block_type = BlockType::Transparent;
+        world.blocks[3].block_type = BlockType::Transparent;
+        let place = |world: &mut World, x: f32, id: u64| world.place_block(Vec3::new(x, 1.0, 1.0), Block::new(id, NbtBlock::new(), world));
+        place(&mut world, 1.0, 1);
+        place(&mut world, 2.0, 2);
+        place(&mut world, 3.0, 2);
+        place(&mut world, 4.0, 3);
+        let faces = |world: &World, x: usize| {
+            let mut faces = world.chunks[0][0][0].get_mesh_data(false).into_iter().find(|b| b.pos.x as usize == x).unwrap();
+            world.cull_faces(&mut faces, Vec3::new(x as f32, 1.0, 1.0));
+            return (faces.left, faces.right, faces.top);
+        };
+        // stone hides the glass next to it, glass hides glass, but ice doesn't hide glass
+        assert_eq!(faces(&world, 2), (false, false, true));
+        assert_eq!(faces(&world, 3), (false, true, true));
+        assert_eq!(faces(&world, 4), (true, true, true));
+    }
+
+    #[test]
+    fn cutout_blocks_are_opaque_but_show_their_neighbours() {
+        let mut world = testing::world(&["stone", "leaves"], &[]);
+        world.blocks[2].block_type = BlockType::Cutout;
+        world.place_block(Vec3::new(1.0, 1.0, 1.0), Block::new(1, NbtBlock::new(), &world));
+        world.place_block(Vec3::new(2.0, 1.0, 1.0), Block::new(2, NbtBlock::new(), &world));
+        let chunk = &world.chunks[0][0][0];
+        assert!(chunk.get_mesh_data(false).is_empty());
+        let mut faces = chunk.get_mesh_data(true).into_iter().find(|b| b.pos.x == 1.0).unwrap();
+        world.cull_faces(&mut faces, Vec3::new(1.0, 1.0, 1.0));
+        // the stone is seen through the holes in the leaves, but the stone hides the leaves' side
+        assert!(faces.right);
+        let mut faces = chunk.get_mesh_data(true).into_iter().find(|b| b.pos.x == 2.0).unwrap();
+        world.cull_faces(&mut faces, Vec3::new(2.0, 1.0, 1.0));
+        assert!(!faces.left && faces.right);
+    }
+
+    #[test]
+    fn plants_are_two_crossed_double_sided_quads() {
+        let world = testing::world(&[], &[]);
+        let plant = PlantModelType {
+            texture: Box::new("grass"),
+            random_offset: false,
+        };
+        let pos = Vec3::new(3.0, 2.0, 3.0);
+        let mut triangles = Vec::new();
+        world.render_plant(&mut triangles, pos, &plant, &TextureLocation::default());
+        // two quads, each twice with opposite windings, of two triangles each
+        assert_eq!(triangles.len(), 8);
+        let normal = |t: &Triangle| (t.p2 - t.p1).cross(&(t.p3 - t.p1)).normalize();
+        for quad in triangles.chunks(4) {
+            assert!((normal(&quad[0]) + normal(&quad[2])).norm() < 1e-4);
+            // every corner is on one of the block's diagonals and spans its height
+            for t in quad {
+                for p in [t.p1, t.p2, t.p3] {
+                    let (x, z) = (p.x - pos.x, p.z - pos.z);
+                    assert!((x - z).abs() < 1e-4 || (x + z).abs() < 1e-4);
+                    assert!(((p.y - pos.y).abs() - 0.5).abs() < 1e-4);
+                }
+            }
+        }
+        // the two quads cross each other
+        assert!(normal(&triangles[0]).dot(&normal(&triangles[4])).abs() < 1e-4);
+    }
+}
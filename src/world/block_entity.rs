@@ -0,0 +1,120 @@
+use std::any::Any;
+
+use rand::Rng;
+
+use crate::container::Menu;
+use crate::inventory::Inventory;
+use crate::loot;
+use crate::nbt::Compound;
+
+use super::*;
+
+// state and behavior for a single block position, like the items in a chest
+pub trait BlockEntity {
+    // runs every tick with the rest of the world
+    fn tick(&mut self, _pos: BlockPos, _world: &mut World) {}
+    // runs once when its block is placed, but not when it is loaded
+    fn placed(&mut self, _pos: BlockPos, _world: &mut World) {}
+    // the items a menu can show and change
+    fn inventory(&self) -> Option<&Inventory> {
+        return None;
+    }
+    fn inventory_mut(&mut self) -> Option<&mut Inventory> {
+        return None;
+    }
+    // what the player sees when using the block
+    fn menu(&self, _pos: BlockPos, _world: &World) -> Option<Menu> {
+        return None;
+    }
+    // 0 to 1 for each of the menu's progress bars
+    fn progress(&self) -> Vec<f32> {
+        return Vec::new();
+    }
+    // the player took items out of one of its result slots
+    fn output_taken(&mut self, _player: &mut Player) {}
+    // the loot table it still has to be filled from and the seed to roll it with, 0 for
+    // a random one, it only gets filled once
+    fn take_loot_table(&mut self) -> Option<(String, u64)> {
+        return None;
+    }
+    // writes what has to survive a save into the block's nbt
+    fn save(&self, nbt: &mut Compound, world: &World);
+    fn load(&mut self, nbt: &Compound, world: &World);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl World {
+    pub fn block_entity(&self, pos: BlockPos) -> Option<&dyn BlockEntity> {
+        return self.block_entities.get(&pos).map(|x| x.as_ref());
+    }
+    pub fn block_entity_mut(&mut self, pos: BlockPos) -> Option<&mut dyn BlockEntity> {
+        return Some(self.block_entities.get_mut(&pos)?.as_mut());
+    }
+    // the block entity at pos if it is a T
+    pub fn block_entity_as<T: 'static>(&mut self, pos: BlockPos) -> Option<&mut T> {
+        return self.block_entities.get_mut(&pos)?.as_any_mut().downcast_mut();
+    }
+    // fills a generated container from its loot table, when it is first opened or broken.
+    // whatever doesn't fit is dropped on top of it
+    pub fn unpack_loot(&mut self, pos: BlockPos) {
+        let Some((name, seed)) = self.block_entity_mut(pos).and_then(|x| x.take_loot_table()) else {
+            return;
+        };
+        let mut rng = StdRng::seed_from_u64(if seed == 0 { self.rng.gen() } else { seed });
+        let Some(table) = self.loot_tables.get(&name) else {
+            eprintln!("unknown loot table {}", name);
+            return;
+        };
+        let stacks = table.roll(&LootContext::default(), &self.items, &mut rng);
+        let Some(inventory) = self.block_entities.get_mut(&pos).and_then(|x| x.inventory_mut()) else {
+            return;
+        };
+        for stack in loot::fill_inventory(inventory, stacks, &mut rng) {
+            self.spawn_item(pos.to_vec(), stack);
+        }
+    }
+    // drops the old block's entity and makes the new one's, loaded from the block's nbt.
+    // placed is for blocks that were just put there, as opposed to loaded from a save,
+    // even when they come with nbt like a generated chest
+    pub(super) fn replace_block_entity(&mut self, pos: BlockPos, placed: bool) {
+        self.block_entities.remove(&pos);
+        let Some(block) = self.block_at(pos.to_vec()) else {
+            return;
+        };
+        if let Some(create) = self.blocks[block.id as usize].block_entity {
+            let mut entity = create();
+            entity.load(&block.nbt.data, self);
+            if placed {
+                entity.placed(pos, self);
+            }
+            self.block_entities.insert(pos, entity);
+        }
+    }
+    // in the same order every time, each entity is taken out while it ticks so it can
+    // change the world, then put back unless its block was replaced in the meantime
+    pub(super) fn tick_block_entities(&mut self) {
+        let mut positions: Vec<BlockPos> = self.block_entities.keys().copied().collect();
+        positions.sort_by_key(|pos| (pos.y, pos.z, pos.x));
+        for pos in positions {
+            let Some(mut entity) = self.block_entities.remove(&pos) else {
+                continue;
+            };
+            let id = self.block_at(pos.to_vec()).map(|x| x.id);
+            entity.tick(pos, self);
+            if self.block_at(pos.to_vec()).map(|x| x.id) == id && !self.block_entities.contains_key(&pos) {
+                self.block_entities.insert(pos, entity);
+            }
+        }
+    }
+    // every block entity's position and nbt, sorted so saves come out the same
+    pub(super) fn block_entity_nbt(&self) -> Vec<(BlockPos, Compound)> {
+        let mut list: Vec<(BlockPos, Compound)> = self.block_entities.iter().map(|(pos, entity)| {
+            let mut nbt = self.block_at(pos.to_vec()).map_or(Compound::new(), |x| x.nbt.data.clone());
+            entity.save(&mut nbt, self);
+            (*pos, nbt)
+        }).collect();
+        list.sort_by_key(|(pos, _)| (pos.y, pos.z, pos.x));
+        return list;
+    }
+}
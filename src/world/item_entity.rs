@@ -0,0 +1,250 @@
+use rand::Rng;
+
+use crate::inventory::Inventory;
+use crate::nbt::Tag;
+use crate::physics::*;
+
+use super::*;
+
+// all of these are in blocks and ticks
+const ITEM_SIZE: f32 = 0.25;
+const GRAVITY: f32 = 0.04;
+const DRAG: f32 = 0.98; // speed kept each tick
+const GROUND_FRICTION: f32 = 0.6; // horizontal speed kept each tick on the ground, on top of the drag
+const PICKUP_DELAY: u32 = 10;
+const LIFETIME: u32 = 6000; // it disappears after 5 minutes
+const MERGE_DISTANCE: f32 = 0.5;
+const VOID_HEIGHT: f32 = -64.0;
+const SPIN_SPEED: f32 = 0.05; // radians
+const BOB_HEIGHT: f32 = 0.1;
+
+// a stack lying in the world until someone walks over it
+#[derive(Clone, Debug)]
+pub struct ItemEntity {
+    pub stack: ItemStack,
+    pub pos: Vec3, // the bottom center
+    pub prev_pos: Vec3,
+    pub velocity: Vec3,
+    pub on_ground: bool,
+    pub age: u32,
+    pub pickup_delay: u32, // ticks before it can be picked up
+}
+
+impl ItemEntity {
+    pub fn new(pos: Vec3, velocity: Vec3, stack: ItemStack) -> Self {
+        return Self {
+            stack,
+            pos,
+            prev_pos: pos,
+            velocity,
+            on_ground: false,
+            age: 0,
+            pickup_delay: PICKUP_DELAY,
+        }
+    }
+    pub fn aabb(&self) -> Aabb {
+        return Aabb::from_feet(self.pos, ITEM_SIZE, ITEM_SIZE);
+    }
+    // the position part way from last tick to this one
+    pub fn pos_at(&self, alpha: f32) -> Vec3 {
+        return self.pos + (self.prev_pos - self.pos) * (1.0 - alpha);
+    }
+    fn tick(&mut self, world: &World) {
+        self.prev_pos = self.pos;
+        self.age += 1;
+        self.pickup_delay = self.pickup_delay.saturating_sub(1);
+        self.velocity.y -= GRAVITY;
+        let (moved, collision) = move_and_collide(world, self.aabb(), self.velocity, 0.0);
+        self.pos += moved;
+        self.on_ground = collision.on_ground;
+        if collision.hit_x {
+            self.velocity.x = 0.0;
+        }
+        if collision.hit_z {
+            self.velocity.z = 0.0;
+        }
+        if collision.on_ground || collision.hit_ceiling {
+            self.velocity.y = 0.0;
+        }
+        let friction = if self.on_ground { GROUND_FRICTION * DRAG } else { DRAG };
+        self.velocity.x *= friction;
+        self.velocity.y *= DRAG;
+        self.velocity.z *= friction;
+    }
+    fn is_dead(&self) -> bool {
+        return self.stack.is_empty() || self.age >= LIFETIME || self.pos.y < VOID_HEIGHT;
+    }
+    pub fn save(&self, world: &World) -> Compound {
+        let mut nbt = Compound::new();
+        let floats = |vec: Vec3| Tag::List(vec![Tag::Float(vec.x), Tag::Float(vec.y), Tag::Float(vec.z)]);
+        nbt.set("item", Tag::Compound(self.stack.save(world)));
+        nbt.set("pos", floats(self.pos));
+        nbt.set("velocity", floats(self.velocity));
+        nbt.set("age", Tag::Int(self.age as i32));
+        nbt.set("pickup_delay", Tag::Int(self.pickup_delay as i32));
+        return nbt;
+    }
+    pub fn load(nbt: &Compound, world: &World) -> Option<Self> {
+        let floats = |name: &str| match nbt.get(name) {
+            Some(Tag::List(list)) => match list.as_slice() {
+                [Tag::Float(x), Tag::Float(y), Tag::Float(z)] => Some(Vec3::new(*x, *y, *z)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let mut entity = Self::new(floats("pos")?, floats("velocity")?, ItemStack::load(nbt.get_compound("item")?, world)?);
+        entity.age = nbt.get_int("age").unwrap_or(0).max(0) as u32;
+        entity.pickup_delay = nbt.get_int("pickup_delay").unwrap_or(0).max(0) as u32;
+        return Some(entity);
+    }
+}
+
+impl World {
+    // drops a stack around the center of a block, popping up a little in a random direction
+    pub fn spawn_item(&mut self, center: Vec3, stack: ItemStack) {
+        let offset = Vec3::new(self.rng.gen_range(-0.25..0.25), self.rng.gen_range(-0.25..0.25) - ITEM_SIZE / 2.0, self.rng.gen_range(-0.25..0.25));
+        let velocity = Vec3::new(self.rng.gen_range(-0.1..0.1), 0.2, self.rng.gen_range(-0.1..0.1));
+        self.item_entities.push(ItemEntity::new(center + offset, velocity, stack));
+    }
+    // puts every item in the area that can be picked up into the inventory as far as it fits,
+    // returns whether anything was picked up
+    pub fn pick_up_items(&mut self, area: &Aabb, inventory: &mut Inventory) -> bool {
+        let mut picked = false;
+        for entity in self.item_entities.iter_mut() {
+            if entity.pickup_delay > 0 || !entity.aabb().intersects(area) {
+                continue;
+            }
+            let count = entity.stack.count;
+            entity.stack.count = inventory.add(entity.stack.clone()).map_or(0, |x| x.count);
+            picked |= entity.stack.count != count;
+        }
+        self.item_entities.retain(|x| !x.stack.is_empty());
+        return picked;
+    }
+    // they are taken out while they move so they can look at the world, then stacks of the
+    // same item close to each other are merged
+    pub(super) fn tick_item_entities(&mut self) {
+        let mut entities = std::mem::take(&mut self.item_entities);
+        for entity in entities.iter_mut() {
+            entity.tick(self);
+        }
+        for i in 0..entities.len() {
+            let (first, rest) = entities[i..].split_first_mut().unwrap();
+            for other in rest.iter_mut() {
+                let close = (first.pos - other.pos).norm() < MERGE_DISTANCE;
+                if close && !first.stack.is_empty() && first.stack.can_merge(&other.stack) {
+                    first.stack.merge(&mut other.stack);
+                    first.age = first.age.min(other.age);
+                    first.pickup_delay = first.pickup_delay.max(other.pickup_delay);
+                }
+            }
+        }
+        entities.retain(|x| !x.is_dead());
+        // anything spawned while they were ticking goes after them
+        entities.append(&mut self.item_entities);
+        self.item_entities = entities;
+    }
+    // every item as a small spinning block or a flat picture of the item, lit by where it is
+    pub fn render_item_entities<T>(&self, vert: &mut Vec<f32>, alpha: f32, window: &Window<T>) {
+        let flat = BlockModelType {
+            block_size: (1.0, 1.0, 0.0),
+            offset: (0.0, 0.0, 0.5),
+            texture: BlockTextureType::None,
+        };
+        for entity in self.item_entities.iter() {
+            let item = &self.items[entity.stack.item as usize];
+            // the sides to draw, with the model they are taken from and their texture
+            let sides: Vec<(Side, &BlockModelType, String)> = match (&item.texture, item.block.map(|x| &self.blocks[x as usize].model)) {
+                (Some(texture), _) => vec![(Side::Front, &flat, texture.get_texture_name()), (Side::Back, &flat, texture.get_texture_name())],
+                (None, Some(ModelType::Block(model))) => match model.texture {
+                    BlockTextureType::None => continue,
+                    _ => Side::ALL.iter().map(|side| (*side, model, model.get_texture(side))).collect(),
+                },
+                (None, Some(ModelType::Plant(plant))) => vec![(Side::Front, &flat, plant.texture.get_texture_name()), (Side::Back, &flat, plant.texture.get_texture_name())],
+                (None, None) => continue,
+            };
+            let time = entity.age as f32 + alpha;
+            let (sin, cos) = (time * SPIN_SPEED).sin_cos();
+            let center = entity.pos_at(alpha) + Vec3::new(0.0, ITEM_SIZE / 2.0 + ((time * SPIN_SPEED * 2.0).sin() + 1.0) * BOB_HEIGHT / 2.0, 0.0);
+            let light = center.round();
+            let (r, g, b) = self.get_block_light(light);
+            let max = MAX_LIGHT as f32;
+            let shade = Shade {
+                ao: 1.0,
+                sky: self.get_sky_light(light) as f32 / max,
+                block: (r as f32 / max, g as f32 / max, b as f32 / max),
+            };
+            let mut triangles = Vec::new();
+            for (side, model, texture) in sides {
+                // the model is centered on zero, then shrunk, spun around and moved to the item
+                let local = generate_face_vertices(Vec3::new(0.0, 0.0, 0.0), model, &side);
+                let uvs = generate_face_uvs(Vec3::new(0.0, 0.0, 0.0), &local, &side);
+                let data = local.map(|v| center + Vec3::new(v.x * cos - v.z * sin, v.y, v.x * sin + v.z * cos) * ITEM_SIZE);
+                Triangle::square_mapped(&mut triangles, Quad { corners: data, uvs, shades: [shade; 4] }, &window.shaders.get_texture(texture.as_str()));
+            }
+            for triangle in triangles.iter() {
+                triangle.to_points(vert);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn world() -> World {
+        let mut world = testing::world(&["stone"], &[]);
+        // wide enough that nothing bounces off it
+        for x in 1..8 {
+            for z in 1..8 {
+                world.place_block(Vec3::new(x as f32, 4.0, z as f32), Block::new(1, NbtBlock::new(), &world));
+            }
+        }
+        return world;
+    }
+
+    #[test]
+    fn drops_land_merge_and_get_picked_up() {
+        let mut world = world();
+        // the drops scatter randomly, a fixed seed keeps them close enough to merge
+        world.rng = StdRng::seed_from_u64(0);
+        world.spawn_item(Vec3::new(4.0, 6.0, 4.0), world.item_stack(1, 10));
+        world.spawn_item(Vec3::new(4.0, 6.0, 4.0), world.item_stack(1, 5));
+        for _ in 0..40 {
+            world.tick();
+        }
+        let items = &world.item_entities;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].stack.count, 15);
+        assert!(items[0].on_ground);
+        assert!((items[0].pos.y - 4.5).abs() < 1e-3);
+
+        // only 12 fit, the rest stays on the ground
+        let mut inventory = Inventory::new(1);
+        inventory.slots[0] = Some(world.item_stack(1, 52));
+        let far = Aabb::from_feet(Vec3::new(10.0, 4.5, 10.0), 0.6, 1.8);
+        assert!(!world.pick_up_items(&far, &mut inventory));
+        let near = Aabb::from_feet(world.item_entities[0].pos, 0.6, 1.8);
+        assert!(world.pick_up_items(&near, &mut inventory));
+        assert_eq!(inventory.slots[0].as_ref().unwrap().count, 64);
+        assert_eq!(world.item_entities[0].stack.count, 3);
+    }
+
+    #[test]
+    fn items_survive_saving() {
+        let mut world = world();
+        world.spawn_item(Vec3::new(4.0, 6.0, 4.0), world.item_stack(1, 7));
+        world.tick();
+        let path = testing::temp_path("item_entity_save_test");
+        world.save(&path).unwrap();
+        let mut loaded = self::world();
+        let result = loaded.load(&path);
+        std::fs::remove_file(&path).ok();
+        result.unwrap();
+        let (saved, loaded) = (&world.item_entities[0], &loaded.item_entities[0]);
+        assert_eq!(loaded.stack, saved.stack);
+        assert_eq!((loaded.pos, loaded.velocity, loaded.age), (saved.pos, saved.velocity, saved.age));
+    }
+}
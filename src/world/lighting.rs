@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+
+use super::*;
+
+pub const MAX_LIGHT: u8 = 15;
+
+
+const CHANNELS: [Channel; 4] = [Channel::Sky, Channel::Red, Channel::Green, Channel::Blue];
+
+// each channel is 4 bits of a voxel's light, sky light on top then red, green and blue block light
+#[derive(Clone, Copy, PartialEq)]
+enum Channel {
+    Sky,
+    Red,
+    Green,
+    Blue,
+}
+
+impl Channel {
+    fn shift(&self) -> u16 {
+        match self {
+            Self::Sky => 12,
+            Self::Red => 8,
+            Self::Green => 4,
+            Self::Blue => 0,
+        }
+    }
+    fn get(&self, light: u16) -> u8 {
+        return (light >> self.shift() & 0x0F) as u8;
+    }
+    fn set(&self, light: u16, level: u8) -> u16 {
+        return (light & !(0x0F << self.shift())) | (level as u16) << self.shift();
+    }
+    // picks this channel out of a block light color
+    fn of(&self, color: (u8, u8, u8)) -> u8 {
+        match self {
+            Self::Sky => 0,
+            Self::Red => color.0,
+            Self::Green => color.1,
+            Self::Blue => color.2,
+        }
+    }
+}
+
+impl World {
+    pub fn get_sky_light(&self, vec: Vec3) -> u8 {
+        return self.get_light(Channel::Sky, vec).unwrap_or(0);
+    }
+    pub fn get_block_light(&self, vec: Vec3) -> (u8, u8, u8) {
+        return (
+            self.get_light(Channel::Red, vec).unwrap_or(0),
+            self.get_light(Channel::Green, vec).unwrap_or(0),
+            self.get_light(Channel::Blue, vec).unwrap_or(0),
+        );
+    }
+    fn get_light(&self, channel: Channel, vec: Vec3) -> Option<u8> {
+        let (chunk, offset) = self.chunk_at(vec)?;
+        return Some(channel.get(chunk.light[offset.x as usize][offset.y as usize][offset.z as usize]));
+    }
+    fn set_light(&mut self, channel: Channel, vec: Vec3, level: u8) {
+        if let Some((chunk, offset)) = self.chunk_at_mut(vec) {
+            let light = &mut chunk.light[offset.x as usize][offset.y as usize][offset.z as usize];
+            *light = channel.set(*light, level);
+        }
+    }
+    // colored blocks like stained glass filter out the block light channels they don't let through
+    fn light_opacity(&self, channel: Channel, vec: Vec3) -> u8 {
+        return match self.block_at(vec) {
+            Some(block) => {
+                let data = &self.blocks[block.id as usize];
+                data.light_opacity.saturating_add(channel.of(data.light_filter)).min(MAX_LIGHT)
+            },
+            None => MAX_LIGHT,
+        };
+    }
+    fn light_emission(&self, channel: Channel, vec: Vec3) -> u8 {
+        return match self.block_at(vec) {
+            Some(block) => channel.of(self.blocks[block.id as usize].light_emission).min(MAX_LIGHT),
+            None => 0,
+        };
+    }
+    // open voxels at the top of the world get full sky light
+    fn sky_source(&self, vec: Vec3) -> u8 {
+        let above = vec.add(Side::Top.normal());
+        if self.chunk_at(above).is_none() && self.light_opacity(Channel::Sky, vec) == 0 {
+            return MAX_LIGHT;
+        }
+        return 0;
+    }
+    // called by place_block after the block at vec has been swapped out
+    pub(super) fn update_light(&mut self, vec: Vec3) {
+        for channel in CHANNELS {
+            let mut queue = self.remove_light(channel, vec);
+            let source = match channel {
+                Channel::Sky => self.sky_source(vec),
+                _ => self.light_emission(channel, vec),
+            };
+            if source > 0 {
+                self.set_light(channel, vec, source);
+                queue.push_back(vec);
+            }
+            // lets the neighbors shine back in if the new block lets more light through
+            for side in Side::ALL.iter() {
+                let neighbor = vec.add(side.normal());
+                if self.get_light(channel, neighbor).unwrap_or(0) > 0 {
+                    queue.push_back(neighbor);
+                }
+            }
+            self.spread_light(channel, queue);
+        }
+    }
+    // recalculates the light of the whole world from scratch, used after loading
+    pub(super) fn relight(&mut self) {
+        for x in self.chunks.iter_mut() {
+            for z in x.iter_mut() {
+                for chunk in z.iter_mut() {
+                    chunk.light = [[[0; 16]; 16]; 16];
+                }
+            }
+        }
+        let width = self.chunks.len() * 16;
+        let depth = self.chunks.first().map_or(0, |x| x.len()) * 16;
+        let height = 16 * 16;
+        for channel in CHANNELS {
+            let mut queue = VecDeque::new();
+            for x in 0..width {
+                for z in 0..depth {
+                    if channel == Channel::Sky {
+                        // sky light falls down each column until something stops it
+                        for y in (0..height).rev() {
+                            let pos = Vec3::new(x as f32, y as f32, z as f32);
+                            if self.light_opacity(channel, pos) != 0 {
+                                break;
+                            }
+                            self.set_light(channel, pos, MAX_LIGHT);
+                            queue.push_back(pos);
+                        }
+                        continue;
+                    }
+                    for y in 0..height {
+                        let pos = Vec3::new(x as f32, y as f32, z as f32);
+                        let emission = self.light_emission(channel, pos);
+                        if emission > 0 {
+                            self.set_light(channel, pos, emission);
+                            queue.push_back(pos);
+                        }
+                    }
+                }
+            }
+            self.spread_light(channel, queue);
+        }
+    }
+    // clears the light that came from vec and returns the voxels that have to fill the hole back in
+    fn remove_light(&mut self, channel: Channel, vec: Vec3) -> VecDeque<Vec3> {
+        let mut refill = VecDeque::new();
+        let mut queue = VecDeque::new();
+        let level = self.get_light(channel, vec).unwrap_or(0);
+        self.set_light(channel, vec, 0);
+        queue.push_back((vec, level));
+        while let Some((pos, level)) = queue.pop_front() {
+            for side in Side::ALL.iter() {
+                let neighbor = pos.add(side.normal());
+                let neighbor_level = match self.get_light(channel, neighbor) {
+                    Some(l) => l,
+                    None => continue,
+                };
+                // full sky light falls straight down without losing a level
+                let from_above = channel == Channel::Sky && matches!(side, Side::Bottom) && level == MAX_LIGHT;
+                if neighbor_level != 0 && (neighbor_level < level || from_above && neighbor_level == MAX_LIGHT) {
+                    self.set_light(channel, neighbor, 0);
+                    queue.push_back((neighbor, neighbor_level));
+                } else if neighbor_level >= level {
+                    refill.push_back(neighbor);
+                }
+            }
+        }
+        return refill;
+    }
+    fn spread_light(&mut self, channel: Channel, mut queue: VecDeque<Vec3>) {
+        while let Some(pos) = queue.pop_front() {
+            let level = self.get_light(channel, pos).unwrap_or(0);
+            if level == 0 {
+                continue;
+            }
+            for side in Side::ALL.iter() {
+                let neighbor = pos.add(side.normal());
+                let neighbor_level = match self.get_light(channel, neighbor) {
+                    Some(l) => l,
+                    None => continue,
+                };
+                let opacity = self.light_opacity(channel, neighbor);
+                let new_level = if channel == Channel::Sky && matches!(side, Side::Bottom) && level == MAX_LIGHT && opacity == 0 {
+                    MAX_LIGHT
+                } else {
+                    level.saturating_sub(opacity.max(1))
+                };
+                if new_level > neighbor_level {
+                    self.set_light(channel, neighbor, new_level);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    const AIR: u64 = 0;
+    const STONE: u64 = 1;
+    const LAMP: u64 = 2;
+
+    fn world() -> World {
+        let mut world = testing::world(&["stone", "lamp"], &[]);
+        world.blocks[STONE as usize].light_opacity = MAX_LIGHT;
+        world.blocks[LAMP as usize].light_emission = (14, 14, 14);
+        return world;
+    }
+
+    fn place(world: &mut World, x: f32, y: f32, z: f32, id: u64) {
+        world.place_block(Vec3::new(x, y, z), Block::new(id, NbtBlock::new(), world));
+    }
+
+    fn light(world: &World, x: f32, y: f32, z: f32) -> u8 {
+        return world.get_block_light(Vec3::new(x, y, z)).0;
+    }
+
+    #[test]
+    fn block_light_falls_off_with_distance() {
+        let mut world = world();
+        place(&mut world, 8.0, 8.0, 8.0, LAMP);
+        assert_eq!(light(&world, 8.0, 8.0, 8.0), 14);
+        assert_eq!(light(&world, 11.0, 8.0, 8.0), 11);
+        assert_eq!(light(&world, 9.0, 9.0, 7.0), 11);
+        assert_eq!(light(&world, 8.0, 8.0, 0.0), 6);
+        assert_eq!(world.get_block_light(Vec3::new(5.0, 8.0, 8.0)), (11, 11, 11));
+    }
+
+    #[test]
+    fn light_is_removed_and_refilled() {
+        let mut world = world();
+        place(&mut world, 8.0, 8.0, 8.0, LAMP);
+        // light has to go around the stone
+        place(&mut world, 10.0, 8.0, 8.0, STONE);
+        assert_eq!(light(&world, 10.0, 8.0, 8.0), 0);
+        assert_eq!(light(&world, 11.0, 8.0, 8.0), 9);
+        place(&mut world, 10.0, 8.0, 8.0, AIR);
+        assert_eq!(light(&world, 10.0, 8.0, 8.0), 12);
+        assert_eq!(light(&world, 11.0, 8.0, 8.0), 11);
+        place(&mut world, 8.0, 8.0, 8.0, AIR);
+        assert_eq!(light(&world, 8.0, 8.0, 8.0), 0);
+        assert_eq!(light(&world, 11.0, 8.0, 8.0), 0);
+    }
+
+    #[test]
+    fn light_spreads_across_chunk_borders() {
+        let mut world = world();
+        let column = core::array::from_fn(|_| Chunk::new(&world));
+        world.chunks.push(vec![column]);
+        place(&mut world, 15.0, 15.0, 8.0, LAMP);
+        assert_eq!(light(&world, 16.0, 15.0, 8.0), 13);
+        assert_eq!(light(&world, 18.0, 15.0, 8.0), 11);
+        assert_eq!(light(&world, 15.0, 17.0, 8.0), 12);
+        assert_eq!(light(&world, 16.0, 16.0, 8.0), 12);
+        place(&mut world, 15.0, 15.0, 8.0, AIR);
+        assert_eq!(light(&world, 16.0, 16.0, 8.0), 0);
+    }
+
+    #[test]
+    fn tinted_blocks_filter_their_channels() {
+        let mut world = world();
+        let mut glass = world.blocks[STONE as usize].clone();
+        glass.name = "red glass".to_string();
+        glass.light_opacity = 0;
+        glass.light_filter = (0, MAX_LIGHT, MAX_LIGHT); // only red gets through
+        let red_glass = world.blocks.len() as u64;
+        world.reg_block(glass);
+        place(&mut world, 8.0, 8.0, 8.0, LAMP);
+        place(&mut world, 9.0, 8.0, 8.0, red_glass);
+        assert_eq!(world.get_block_light(Vec3::new(9.0, 8.0, 8.0)), (13, 0, 0));
+        // green and blue have to go around it
+        assert_eq!(world.get_block_light(Vec3::new(10.0, 8.0, 8.0)), (12, 10, 10));
+    }
+
+    #[test]
+    fn sky_light_falls_straight_down() {
+        let mut world = world();
+        world.relight();
+        for y in [255.0, 100.0, 0.0] {
+            assert_eq!(world.get_sky_light(Vec3::new(8.0, y, 8.0)), MAX_LIGHT);
+        }
+        // under a roof it only comes in from the sides, which are 4 blocks away
+        for x in 5..12 {
+            for z in 5..12 {
+                place(&mut world, x as f32, 100.0, z as f32, STONE);
+            }
+        }
+        assert_eq!(world.get_sky_light(Vec3::new(8.0, 100.0, 8.0)), 0);
+        assert_eq!(world.get_sky_light(Vec3::new(8.0, 99.0, 8.0)), 11);
+        assert_eq!(world.get_sky_light(Vec3::new(8.0, 0.0, 8.0)), 11);
+        // and a hole lets it down to the bottom again
+        place(&mut world, 8.0, 100.0, 8.0, AIR);
+        assert_eq!(world.get_sky_light(Vec3::new(8.0, 99.0, 8.0)), MAX_LIGHT);
+        assert_eq!(world.get_sky_light(Vec3::new(8.0, 0.0, 8.0)), MAX_LIGHT);
+    }
+}
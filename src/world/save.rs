@@ -0,0 +1,205 @@
+use std::{fs, io::{self, Read, Write}, path::Path};
+
+use super::*;
+
+const MAGIC: &[u8; 4] = b"MCCW";
+const VERSION: u32 = 2;
+
+// the world is stored as its time, then a palette of block names so ids can change
+// between versions, then every section's blocks as indices into the palette, then
+// the nbt of every block entity by position, then the nbt of every item lying around
+impl World {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut data = Vec::new();
+        data.extend_from_slice(MAGIC);
+        write_u32(&mut data, VERSION);
+        write_u64(&mut data, self.time);
+        write_u64(&mut data, self.day_length);
+
+        write_u32(&mut data, self.blocks.len() as u32);
+        for block in self.blocks.iter() {
+            write_string(&mut data, &block.name);
+        }
+
+        write_u32(&mut data, self.chunks.len() as u32);
+        write_u32(&mut data, self.chunks.first().map_or(0, |x| x.len()) as u32);
+        for x in self.chunks.iter() {
+            for z in x.iter() {
+                for chunk in z.iter() {
+                    for a in chunk.blocks.iter() {
+                        for b in a.iter() {
+                            for block in b.iter() {
+                                write_u32(&mut data, block.id as u32);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let block_entities = self.block_entity_nbt();
+        write_u32(&mut data, block_entities.len() as u32);
+        for (pos, nbt) in block_entities.iter() {
+            for value in [pos.x, pos.y, pos.z] {
+                write_u32(&mut data, value as u32);
+            }
+            nbt.write(&mut data);
+        }
+
+        write_u32(&mut data, self.item_entities.len() as u32);
+        for entity in self.item_entities.iter() {
+            entity.save(self).write(&mut data);
+        }
+
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(path)?;
+        file.write_all(&data)?;
+        return Ok(());
+    }
+    // the blocks have to be registered before loading, unknown blocks are loaded as air
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> io::Result<()> {
+        let mut data = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut data)?;
+        let mut reader = Reader { data: &data, pos: 0 };
+
+        if reader.take(4)? != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a world save"));
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported save version {}", version)));
+        }
+        // everything is read before the world changes, so a broken save leaves it as it was
+        let time = reader.u64()?;
+        let day_length = reader.u64()?.max(1);
+
+        let mut palette = Vec::new();
+        for _ in 0..reader.u32()? {
+            let name = reader.string()?;
+            palette.push(self.blocks.iter().position(|b| b.name == name).unwrap_or(0) as u64);
+        }
+
+        let width = reader.u32()? as usize;
+        let depth = reader.u32()? as usize;
+        let mut chunks = Vec::new();
+        let mut block_entities = Vec::new();
+        for chunk_x in 0..width {
+            let mut column = Vec::new();
+            for chunk_z in 0..depth {
+                let mut sections: [Chunk; 16] = core::array::from_fn(|_| Chunk::new(self));
+                for (section, chunk) in sections.iter_mut().enumerate() {
+                    for x in 0..16 {
+                        for y in 0..16 {
+                            for z in 0..16 {
+                                let id = *palette.get(reader.u32()? as usize).unwrap_or(&0);
+                                let offset = Vec3::new(x as f32, y as f32, z as f32);
+                                chunk.add_block(offset, &self.blocks[id as usize].block_type);
+                                chunk.blocks[x][y][z] = Block::new(id, NbtBlock::new(), self);
+                                if self.blocks[id as usize].block_entity.is_some() {
+                                    block_entities.push(BlockPos::new((chunk_x * 16 + x) as i32, (section * 16 + y) as i32, (chunk_z * 16 + z) as i32));
+                                }
+                            }
+                        }
+                    }
+                }
+                column.push(sections);
+            }
+            chunks.push(column);
+        }
+
+        let mut block_nbt = Vec::new();
+        for _ in 0..reader.u32()? {
+            let pos = BlockPos::new(reader.u32()? as i32, reader.u32()? as i32, reader.u32()? as i32);
+            block_nbt.push((pos, Compound::read(reader.data, &mut reader.pos)?));
+        }
+
+        let mut item_entities = Vec::new();
+        for _ in 0..reader.u32()? {
+            let nbt = Compound::read(reader.data, &mut reader.pos)?;
+            item_entities.extend(ItemEntity::load(&nbt, self));
+        }
+
+        self.time = time;
+        self.day_length = day_length;
+        self.chunks = chunks;
+        for (pos, nbt) in block_nbt {
+            if let Some(block) = self.get_block(pos.to_vec()) {
+                block.nbt.data = nbt;
+            }
+        }
+        self.block_entities.clear();
+        for pos in block_entities {
+            self.replace_block_entity(pos, false);
+        }
+        self.item_entities = item_entities;
+        self.relight();
+        return Ok(());
+    }
+}
+
+fn write_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_u64(data: &mut Vec<u8>, value: u64) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_string(data: &mut Vec<u8>, value: &str) {
+    write_u32(data, value.len() as u32);
+    data.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.pos + len > self.data.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "save ended early"));
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        return Ok(slice);
+    }
+    fn u32(&mut self) -> io::Result<u32> {
+        return Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+    }
+    fn u64(&mut self) -> io::Result<u64> {
+        return Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()));
+    }
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.u32()? as usize;
+        return String::from_utf8(self.take(len)?.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[test]
+    fn a_broken_save_leaves_the_world_as_it_was() {
+        let mut world = testing::world(&["stone"], &[]);
+        world.place_block(Vec3::new(1.0, 1.0, 1.0), Block::new(1, NbtBlock::new(), &world));
+        let path = testing::temp_path("broken_save_test");
+        world.save(&path).unwrap();
+        let data = fs::read(&path).unwrap();
+        fs::write(&path, &data[..data.len() / 2]).unwrap();
+
+        let mut other = testing::world(&["stone"], &[]);
+        other.time = 1234;
+        other.place_block(Vec3::new(2.0, 1.0, 1.0), Block::new(1, NbtBlock::new(), &other));
+        let result = other.load(&path);
+        fs::remove_file(&path).ok();
+        assert!(result.is_err());
+        assert_eq!(other.time, 1234);
+        assert_eq!(other.block_at(Vec3::new(2.0, 1.0, 1.0)).unwrap().get_id(), 1);
+        assert_eq!(other.block_at(Vec3::new(1.0, 1.0, 1.0)).unwrap().get_id(), 0);
+    }
+}
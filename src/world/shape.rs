@@ -0,0 +1,112 @@
+use super::*;
+
+// a shape made of boxes inside a block, from 0 to 1 on each axis
+#[derive(Clone)]
+pub struct VoxelShape {
+    pub boxes: Vec<BlockCollision>,
+}
+
+impl VoxelShape {
+    pub fn empty() -> Self {
+        return Self {
+            boxes: Vec::new(),
+        }
+    }
+    pub fn full() -> Self {
+        return Self::cuboid(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+    }
+    pub fn cuboid(min: Vec3, max: Vec3) -> Self {
+        return Self {
+            boxes: vec![BlockCollision::new(min, max)],
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        return self.boxes.is_empty();
+    }
+    // the space inside either shape
+    pub fn union(&self, other: &VoxelShape) -> Self {
+        let mut boxes = self.boxes.clone();
+        boxes.extend_from_slice(&other.boxes);
+        return Self {
+            boxes,
+        }
+    }
+    // the space inside both shapes
+    pub fn intersection(&self, other: &VoxelShape) -> Self {
+        let mut boxes = Vec::new();
+        for a in self.boxes.iter() {
+            for b in other.boxes.iter() {
+                let min = a.min().sup(&b.min());
+                let max = a.max().inf(&b.max());
+                if min.x < max.x && min.y < max.y && min.z < max.z {
+                    boxes.push(BlockCollision::new(min, max));
+                }
+            }
+        }
+        return Self {
+            boxes,
+        }
+    }
+    // the boxes in the world for a block centered on pos
+    pub fn boxes_at(&self, pos: Vec3) -> Vec<Aabb> {
+        let corner = pos.sub(Vec3::new(0.5, 0.5, 0.5));
+        return self.boxes.iter().map(|b| Aabb::new(corner.add(b.min()), corner.add(b.max()))).collect();
+    }
+    // whether other, in the block on the face side of this one, hides all of this shape's face there
+    pub fn face_covered_by(&self, face: Side, other: &VoxelShape) -> bool {
+        let mine = self.face_rects(face);
+        if mine.is_empty() {
+            return false;
+        }
+        let theirs = other.face_rects(face.opposite());
+        // splits the face along every rectangle edge, each piece is then either fully
+        // inside a rectangle or fully outside it
+        let mut us: Vec<f32> = mine.iter().chain(theirs.iter()).flat_map(|r| [r.0, r.2]).collect();
+        let mut vs: Vec<f32> = mine.iter().chain(theirs.iter()).flat_map(|r| [r.1, r.3]).collect();
+        us.sort_by(|a, b| a.total_cmp(b));
+        vs.sort_by(|a, b| a.total_cmp(b));
+        us.dedup();
+        vs.dedup();
+        for u in us.windows(2) {
+            for v in vs.windows(2) {
+                let (cu, cv) = ((u[0] + u[1]) / 2.0, (v[0] + v[1]) / 2.0);
+                let inside = |r: &(f32, f32, f32, f32)| cu > r.0 && cu < r.2 && cv > r.1 && cv < r.3;
+                if mine.iter().any(inside) && !theirs.iter().any(inside) {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+    // rectangles of the boxes touching the face side of the block, flattened onto the other two axes
+    fn face_rects(&self, face: Side) -> Vec<(f32, f32, f32, f32)> {
+        let axis = face.axis();
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        return self.boxes.iter()
+            .filter(|x| face.on_edge(x.min(), x.max()))
+            .map(|x| (x.min()[a], x.min()[b], x.max()[a], x.max()[b]))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stairs_are_a_union_of_two_slabs() {
+        let bottom = VoxelShape::cuboid(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.5, 1.0));
+        let step = VoxelShape::cuboid(Vec3::new(0.0, 0.5, 0.5), Vec3::new(1.0, 1.0, 1.0));
+        let stairs = bottom.union(&step);
+        assert_eq!(stairs.boxes.len(), 2);
+        // the back is covered by both halves together, the front only half way
+        assert!(VoxelShape::full().face_covered_by(Side::Front, &stairs));
+        assert!(!VoxelShape::full().face_covered_by(Side::Back, &stairs));
+        assert!(!VoxelShape::full().face_covered_by(Side::Front, &bottom));
+        // the step is all of the stairs' top half
+        let top = VoxelShape::cuboid(Vec3::new(0.0, 0.5, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let inside = stairs.intersection(&top);
+        assert_eq!(inside.boxes.len(), 1);
+        assert_eq!((inside.boxes[0].min(), inside.boxes[0].max()), (step.boxes[0].min(), step.boxes[0].max()));
+    }
+}
@@ -0,0 +1,59 @@
+use std::f32::consts::PI;
+
+use super::*;
+
+pub const DEFAULT_DAY_LENGTH: u64 = 24000;
+
+// time of day the colors are for, 0 is sunrise, 0.25 noon, 0.5 sunset and 0.75 midnight
+const SKY_COLORS: [(f32, (f32, f32, f32)); 5] = [
+    (0.0, (0.85, 0.55, 0.4)), // dawn
+    (0.1, (0.47, 0.65, 1.0)), // day
+    (0.4, (0.47, 0.65, 1.0)),
+    (0.5, (0.8, 0.4, 0.3)), // dusk
+    (0.6, (0.02, 0.02, 0.08)), // night
+];
+
+const FOG_COLORS: [(f32, (f32, f32, f32)); 5] = [
+    (0.0, (0.9, 0.65, 0.5)),
+    (0.1, (0.75, 0.85, 1.0)),
+    (0.4, (0.75, 0.85, 1.0)),
+    (0.5, (0.85, 0.5, 0.4)),
+    (0.6, (0.04, 0.04, 0.1)),
+];
+
+impl World {
+    // from 0 to 1 over a day
+    pub fn time_of_day(&self) -> f32 {
+        return (self.time % self.day_length) as f32 / self.day_length as f32;
+    }
+    // how much of the sky light is lit up, never fully dark so the night can still be seen
+    pub fn sky_brightness(&self) -> f32 {
+        let angle = (self.time_of_day() - 0.25) * 2.0 * PI;
+        let daylight = (angle.cos() * 2.0 + 0.5).clamp(0.0, 1.0);
+        return 0.2 + daylight * 0.8;
+    }
+    pub fn sky_color(&self) -> Vec3 {
+        return color_at(&SKY_COLORS, self.time_of_day());
+    }
+    pub fn fog_color(&self) -> Vec3 {
+        return color_at(&FOG_COLORS, self.time_of_day());
+    }
+}
+
+// blends between the two colors around time, wrapping from night back to dawn
+fn color_at(colors: &[(f32, (f32, f32, f32))], time: f32) -> Vec3 {
+    for i in 0..colors.len() {
+        let (start, a) = colors[i];
+        let (end, b) = if i + 1 < colors.len() {
+            colors[i + 1]
+        } else {
+            (colors[0].0 + 1.0, colors[0].1)
+        };
+        if time >= start && time < end {
+            let t = (time - start) / (end - start);
+            return Vec3::new(a.0, a.1, a.2).lerp(&Vec3::new(b.0, b.1, b.2), t);
+        }
+    }
+    let (_, a) = colors[0];
+    return Vec3::new(a.0, a.1, a.2);
+}